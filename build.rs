@@ -4,6 +4,10 @@ use std::{
     process::Command,
 };
 
+#[allow(dead_code)]
+#[path = "src/shaders/payload.rs"]
+mod payload;
+
 fn main() -> io::Result<()> {
     bundle_tpf()?;
     compile_shaders()?;
@@ -40,8 +44,16 @@ fn bundle_tpf() -> io::Result<()> {
 
 fn compile_shaders() -> io::Result<()> {
     println!("cargo::rerun-if-changed=shaders/ToneMap_PostHook.hlsl");
+    println!("cargo::rerun-if-changed=src/shaders/payload.rs");
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out = format!("{out_dir}/ToneMap_PostHook.ppo");
+
+    fs::write(
+        format!("{out_dir}/ErfpsPayload.hlsli"),
+        payload::ShaderPayload::hlsl_declaration(),
+    )?;
 
-    let out = format!("{}/ToneMap_PostHook.ppo", env::var("OUT_DIR").unwrap());
     let dxc = env::var("DXC_PATH").unwrap_or_else(|_| "dxc".to_owned());
 
     Command::new(dxc)
@@ -50,6 +62,8 @@ fn compile_shaders() -> io::Result<()> {
             "PSMain",
             "-T",
             "ps_6_0",
+            "-I",
+            &out_dir,
             "-Fo",
             &out,
             "shaders/ToneMap_PostHook.hlsl",
@@ -1,3 +1,6 @@
+// Generated by "build.rs" from "src/shaders/payload.rs".
+#include "ErfpsPayload.hlsli"
+
 Texture2D<float4> g_SourceTexture : register(t0);
 
 cbuffer cbPostProcessCommon : register(b4)
@@ -15,7 +18,7 @@ cbuffer cbPostProcessCommon : register(b4)
 cbuffer cbToneMap : register(b1)
 {
     float3 g_ToneMapInvSceneLumScale;
-    int g_ErfpsFlags;
+    uint g_ErfpsFlags;
     float4 g_ReinhardParam;
     float4 g_ToneMapParam;
     float4 g_ToneMapSceneLumScale;
@@ -29,9 +32,10 @@ cbuffer cbToneMap : register(b1)
     float4x3 g_mtxColorMultiplyer;
     float4 g_vChromaticAberrationRG;
     float2 g_vChromaticAberrationB;
-    float2 g_ErfpsCorrectParam;
+    uint2 g_ErfpsParams;
     int4 g_bEnableFlags;
     float4 g_vFeedBackBlurParam;
+    // Read by an earlier tone map pass, this one draws no vignette.
     float4 g_vVignettingParam;
     float4 g_vHDRDisplayParam;
     float4 g_vChromaticAberrationShapeParam;
@@ -40,18 +44,76 @@ cbuffer cbToneMap : register(b1)
     int4 g_vMaxSampleCount;
     float4 g_vScenePreExposure;
     float2 g_vCameraParam;
-    float2 g_ErfpsCrosshairScaleReciprocal;
+    uint2 g_ErfpsMarkers;
 };
 
 SamplerState SS_ClampLinear : register(s1);
 
+// Settings forwarded by erfps2 in the padding of `cbToneMap`.
+uint ErfpsFlag(uint offset, uint bits)
+{
+    return ErfpsBits(g_ErfpsFlags, offset, bits);
+}
+
+float ErfpsFlagUnorm(uint offset, uint bits)
+{
+    return ErfpsUnorm(g_ErfpsFlags, offset, bits);
+}
+
+float2 FovCorrectionParams()
+{
+    uint params = g_ErfpsParams.x;
+    return f16tof32(uint2(ErfpsBits(params, ERFPS_FOV_CORRECTION_X), ErfpsBits(params, ERFPS_FOV_CORRECTION_Y)));
+}
+
+float2 CrosshairScaleReciprocal()
+{
+    uint params = g_ErfpsParams.y;
+    float2 steps = float2(ErfpsBits(params, ERFPS_CROSSHAIR_SCALE_X), ErfpsBits(params, ERFPS_CROSSHAIR_SCALE_Y));
+    return exp2(-(steps / ERFPS_CROSSHAIR_SCALE_STEPS + ERFPS_CROSSHAIR_SCALE_LOG2_MIN));
+}
+
+float3 CrosshairColor()
+{
+    uint params = g_ErfpsParams.y;
+    return float3(
+        ErfpsUnorm(params, ERFPS_CROSSHAIR_RED),
+        ErfpsUnorm(params, ERFPS_CROSSHAIR_GREEN),
+        ErfpsUnorm(params, ERFPS_CROSSHAIR_BLUE)
+    );
+}
+
+float3 CrosshairOutlineColor()
+{
+    uint params = g_ErfpsMarkers.y;
+    return float3(
+        ErfpsUnorm(params, ERFPS_OUTLINE_RED),
+        ErfpsUnorm(params, ERFPS_OUTLINE_GREEN),
+        ErfpsUnorm(params, ERFPS_OUTLINE_BLUE)
+    );
+}
+
+// Direction (radians clockwise from the top of the screen) and opacity of a marker.
+float2 ErfpsMarker(uint markers, uint i)
+{
+    uint marker = markers >> (i * ERFPS_MARKER_STRIDE);
+    float angle = float(ErfpsBits(marker, ERFPS_MARKER_ANGLE)) * (6.2831853 / ERFPS_MARKER_ANGLE_STEPS);
+    return float2(angle, ErfpsUnorm(marker, ERFPS_MARKER_ALPHA));
+}
+
+// Angular distance between two directions.
+float AngleDelta(float a, float b)
+{
+    return abs(frac((a - b) / 6.2831853 + 0.5) - 0.5) * 6.2831853;
+}
+
 // Simple fisheye distortion shader.
 float2 MapUvFisheye(float2 uv)
 {
     float2 c = uv - 0.5;
 
     float r2 = c.x * c.x;
-    float strength = g_ErfpsCorrectParam.y;
+    float strength = FovCorrectionParams().y;
 
     float f = 1.0 + strength * sqrt(r2) * r2;
     float fMax = 1.0 + strength * 0.125;
@@ -80,9 +142,9 @@ float2 MapUvFisheye(float2 uv)
 float2 MapUvBarrel(float2 uv)
 {
     float aspectRatio = g_vCameraParam.x;
-    float cylindricalRatio = g_ErfpsCorrectParam.x;
+    float cylindricalRatio = FovCorrectionParams().x;
 
-    float scaledHeight = g_ErfpsCorrectParam.y * g_vCameraParam.y;
+    float scaledHeight = FovCorrectionParams().y * g_vCameraParam.y;
     float cylAspectRatio = aspectRatio * cylindricalRatio;
     float aspectDiagSq = aspectRatio * aspectRatio + 1.0;
     float diagSq = scaledHeight * scaledHeight * aspectDiagSq;
@@ -99,66 +161,395 @@ float2 MapUvBarrel(float2 uv)
     return uvp.xy / uvp.z;
 }
 
-float CrosshairAlpha(float2 uv)
+// Radius of a perspective projection from a point d behind the center of the view sphere
+// (d = 0 is rectilinear, d = 1 is stereographic).
+float PerspectiveRadius(float cosAngle, float d)
 {
-    float2 c = (uv - 0.5) * g_ErfpsCrosshairScaleReciprocal;
-    float2 cScreen = c * float2(g_vCameraParam.x, 1.0) * g_dynamicScreenPercentage;
+    return (d + 1.0) * sqrt(1.0 - cosAngle * cosAngle) / (d + cosAngle);
+}
+
+// Inverse of `PerspectiveRadius`.
+float PerspectiveCos(float r, float d)
+{
+    float r2 = r * r;
+    float a = r2 + (d + 1.0) * (d + 1.0);
+    float disc = r2 * r2 * d * d - a * (r2 * d * d - (d + 1.0) * (d + 1.0));
+    return (sqrt(max(disc, 0.0)) - r2 * d) / a;
+}
+
+// Projection plane coordinates of the output screen, covering the horizontal FOV.
+float2 ProjectionCoords(float2 uv, float edge)
+{
+    return (uv * 2.0 - 1.0) * float2(1.0, 1.0 / g_vCameraParam.x) * edge;
+}
+
+// Source screen coordinates of rectilinear coordinates.
+float2 RectilinearUv(float2 rect)
+{
+    float halfWidth = FovCorrectionParams().y;
+    return rect / (float2(1.0, 1.0 / g_vCameraParam.x) * halfWidth) * 0.5 + 0.5;
+}
+
+// Panini projection, with the distance as the strength.
+float2 MapUvPanini(float2 uv)
+{
+    float d = FovCorrectionParams().x;
+    float halfFov = atan(FovCorrectionParams().y);
+
+    float2 p = ProjectionCoords(uv, PerspectiveRadius(cos(halfFov), d));
+
+    float c = PerspectiveCos(abs(p.x), d);
+    float s = (d + 1.0) / (d + c);
+
+    float2 rect = float2(sign(p.x) * sqrt(1.0 - c * c), p.y / s) / c;
+    return RectilinearUv(rect);
+}
+
+// General perspective projection, stereographic at full strength.
+float2 MapUvStereographic(float2 uv)
+{
+    float d = FovCorrectionParams().x;
+    float halfFov = atan(FovCorrectionParams().y);
+
+    float2 p = ProjectionCoords(uv, PerspectiveRadius(cos(halfFov), d));
+
+    float r = length(p);
+    if (r < 1e-6) {
+        return uv;
+    }
+
+    float c = PerspectiveCos(r, d);
+
+    float2 rect = p * sqrt(1.0 - c * c) / (c * r);
+    return RectilinearUv(rect);
+}
+
+// Cylindrical projection, equidistant horizontally at full strength.
+float2 MapUvCylindrical(float2 uv)
+{
+    float k = max(1.0 - FovCorrectionParams().x, 1e-3);
+    float halfFov = atan(FovCorrectionParams().y);
+
+    float2 p = ProjectionCoords(uv, tan(k * halfFov) / k);
+
+    float longitude = atan(k * p.x) / k;
+    float c = cos(longitude);
+
+    float2 rect = float2(tan(longitude), p.y * pow(c, k) / c);
+    return RectilinearUv(rect);
+}
+
+float2 MapUv(float2 uv)
+{
+    uint projection = ErfpsFlag(ERFPS_FOV_PROJECTION);
+    switch (projection) {
+        default:
+            return MapUvFisheye(uv);
+        case 1:
+            return MapUvBarrel(uv);
+        case 2:
+            return MapUvPanini(uv);
+        case 3:
+            return MapUvStereographic(uv);
+        case 4:
+            return MapUvCylindrical(uv);
+    }
+}
+
+// Local magnification of the FOV correction, from the change in area of `MapUv`.
+float Magnification(float2 uv, float2 mapped)
+{
+    const float e = 1.0 / 1024.0;
+
+    float2 dx = (MapUv(uv + float2(e, 0.0)) - mapped) / e;
+    float2 dy = (MapUv(uv + float2(0.0, e)) - mapped) / e;
+
+    return rsqrt(max(abs(dx.x * dy.y - dx.y * dy.x), 1e-6));
+}
+
+float2 CrosshairScreenCoords(float2 uv)
+{
+    float2 c = (uv - 0.5) * CrosshairScaleReciprocal();
+    return c * float2(g_vCameraParam.x, 1.0) * g_dynamicScreenPercentage;
+}
+
+// Signed distance to a box centered on the origin.
+float SdBox(float2 p, float2 halfSize)
+{
+    float2 d = abs(p) - halfSize;
+    return length(max(d, 0.0)) + min(max(d.x, d.y), 0.0);
+}
+
+// Fill and outline coverage of the "custom" crosshair, with sizes in pixels at 1080p.
+float2 CustomCrosshairAlpha(float2 c)
+{
+    uint shape = g_ErfpsMarkers.y;
+    float px = 1.0 / 1080.0;
+
+    float dotRadius = float(ErfpsBits(shape, ERFPS_CROSSHAIR_DOT)) * px;
+    float lineLength = float(ErfpsBits(shape, ERFPS_CROSSHAIR_LENGTH)) * px;
+    float lineGap = float(ErfpsBits(shape, ERFPS_CROSSHAIR_GAP)) * px;
+    float halfThickness = float(ErfpsBits(shape, ERFPS_CROSSHAIR_THICKNESS) + 1) * px * 0.5;
+    float ringRadius = float(ErfpsBits(shape, ERFPS_CROSSHAIR_RING)) * px;
+    float outlineWidth = float(ErfpsFlag(ERFPS_CROSSHAIR_OUTLINE)) * px;
+
+    // Half a pixel, the distance field is too discontinuous for `fwidth`.
+    float2 w = fwidth(c);
+    float d = max(w.x, w.y) * 0.5;
+
+    // Rotate the crosshair clockwise, in eighths of a turn.
+    float s, co;
+    sincos(float(ErfpsFlag(ERFPS_CROSSHAIR_ROTATION)) * (6.2831853 / 8.0), s, co);
+    c = float2(c.x * co + c.y * s, c.y * co - c.x * s);
+
+    float dist = 1.0;
 
-    int crosshairKind = (g_ErfpsFlags >> 2) & 7;
+    if (dotRadius > 0.0) {
+        dist = min(dist, length(c) - dotRadius);
+    }
+
+    if (lineLength > 0.0) {
+        float2 halfSize = float2(lineLength * 0.5, halfThickness);
+        float2 a = abs(c);
+        float center = lineGap + halfSize.x;
+
+        float horizontal = SdBox(float2(a.x - center, c.y), halfSize);
+        float vertical = SdBox(float2(a.y - center, c.x), halfSize);
+
+        // "T" style has no top line.
+        if (ErfpsFlag(ERFPS_CROSSHAIR_T_STYLE) != 0 && c.y < 0.0) {
+            vertical = 1.0;
+        }
+
+        dist = min(dist, min(horizontal, vertical));
+    }
+
+    if (ringRadius > 0.0) {
+        dist = min(dist, abs(length(c) - ringRadius) - halfThickness);
+    }
+
+    float fill = 1.0 - smoothstep(-d, d, dist);
+    float outlined = 1.0 - smoothstep(-d, d, dist - outlineWidth);
+
+    return float2(fill, max(outlined - fill, 0.0));
+}
+
+// Fill and outline coverage of the crosshair.
+float2 CrosshairAlpha(float2 uv)
+{
+    float2 cScreen = CrosshairScreenCoords(uv);
+
+    uint crosshairKind = ErfpsFlag(ERFPS_CROSSHAIR_KIND);
     switch (crosshairKind) {
         default:
             return 0.0;
         case 1: {
             cScreen = abs(cScreen);
-            return any(cScreen < 0.0013) && all(cScreen < 0.007) ? 1.0 : 0.0;
+            return float2(any(cScreen < 0.0013) && all(cScreen < 0.007) ? 1.0 : 0.0, 0.0);
         }
         case 2: {
             float r = length(cScreen) / 0.0018;
             float d = fwidth(r);
             float a = 1.0 - smoothstep(1.0 - d, 1.0 + d, r);
-            return a;
+            return float2(a, 0.0);
         }
         case 3: {
             float2 r = length(cScreen) / float2(0.0066, 0.008);
             float2 d = fwidth(r);
             float2 a = 1.0 - smoothstep(1.0 - d, 1.0 + d, r);
-            return a.y - a.x;
+            return float2(a.y - a.x, 0.0);
         }
         case 4: {
             float3 r = length(cScreen) / float3(0.0066, 0.008, 0.0012);
             float3 d = fwidth(r);
             float3 a = 1.0 - smoothstep(1.0 - d, 1.0 + d, r);
-            return a.y - a.x + a.z;
+            return float2(a.y - a.x + a.z, 0.0);
         }
         case 5: {
             cScreen = abs(cScreen);
             float s = cScreen.x + cScreen.y * 3.0;
             float d = fwidth(s);
             float a = smoothstep(0.015 - d, 0.0175, s) - smoothstep(0.0175, 0.02 + d, s);
-            return cScreen.x > 0.007 ? a : 0.0;
+            return float2(cScreen.x > 0.007 ? a : 0.0, 0.0);
         }
+        case 6:
+            return CustomCrosshairAlpha(cScreen);
+    }
+}
+
+// Crosshair alpha, with an outline around the built-in crosshairs for "solid_outline" blend.
+float2 CrosshairCoverage(float2 uv)
+{
+    float2 alpha = CrosshairAlpha(uv);
+
+    uint crosshairBlend = ErfpsFlag(ERFPS_CROSSHAIR_BLEND);
+
+    // The "custom" crosshair has its own outline width.
+    if (crosshairBlend == 2 && ErfpsFlag(ERFPS_CROSSHAIR_KIND) != 6) {
+        float2 px = fwidth(uv);
+        float2 dx = float2(px.x, 0.0);
+        float2 dy = float2(0.0, px.y);
+
+        float dilated = max(
+            max(CrosshairAlpha(uv + dx).x, CrosshairAlpha(uv - dx).x),
+            max(CrosshairAlpha(uv + dy).x, CrosshairAlpha(uv - dy).x)
+        );
+
+        alpha.y = max(dilated - alpha.x, 0.0);
+    }
+
+    return alpha;
+}
+
+// Blends the crosshair over the scene color.
+float3 CrosshairBlend(float3 rgb, float2 alpha)
+{
+    uint crosshairBlend = ErfpsFlag(ERFPS_CROSSHAIR_BLEND);
+    float opacity = ErfpsFlagUnorm(ERFPS_CROSSHAIR_OPACITY);
+
+    float3 crosshairColor = CrosshairColor();
+    float3 fillColor = crosshairBlend == 0 ? 1.0 - rgb : crosshairColor;
+    float outline = crosshairBlend == 2 ? alpha.y : 0.0;
+
+    rgb = lerp(rgb, CrosshairOutlineColor(), outline * opacity);
+    rgb = lerp(rgb, fillColor, alpha.x * opacity);
+
+    return rgb;
+}
+
+// Catmull-Rom bicubic sample, using 9 bilinear taps.
+float4 SampleBicubic(float2 uv)
+{
+    float2 texSize = 1.0 / g_texSizeReciprocal;
+
+    float2 samplePos = uv * texSize;
+    float2 texPos1 = floor(samplePos - 0.5) + 0.5;
+    float2 f = samplePos - texPos1;
+
+    float2 w0 = f * (-0.5 + f * (1.0 - 0.5 * f));
+    float2 w1 = 1.0 + f * f * (-2.5 + 1.5 * f);
+    float2 w2 = f * (0.5 + f * (2.0 - 1.5 * f));
+    float2 w3 = f * f * (-0.5 + 0.5 * f);
+
+    float2 w12 = w1 + w2;
+    float2 texPos0 = (texPos1 - 1.0) * g_texSizeReciprocal;
+    float2 texPos3 = (texPos1 + 2.0) * g_texSizeReciprocal;
+    float2 texPos12 = (texPos1 + w2 / w12) * g_texSizeReciprocal;
+
+    float4 rgba = 0.0;
+
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos0.x, texPos0.y), 0) * w0.x * w0.y;
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos12.x, texPos0.y), 0) * w12.x * w0.y;
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos3.x, texPos0.y), 0) * w3.x * w0.y;
+
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos0.x, texPos12.y), 0) * w0.x * w12.y;
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos12.x, texPos12.y), 0) * w12.x * w12.y;
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos3.x, texPos12.y), 0) * w3.x * w12.y;
+
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos0.x, texPos3.y), 0) * w0.x * w3.y;
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos12.x, texPos3.y), 0) * w12.x * w3.y;
+    rgba += g_SourceTexture.SampleLevel(SS_ClampLinear, float2(texPos3.x, texPos3.y), 0) * w3.x * w3.y;
+
+    return max(rgba, 0.0);
+}
+
+// Scene color, optionally bicubic and contrast adaptive sharpened (after AMD FidelityFX CAS).
+float4 SampleScene(float2 uv, float2 texEdge, float sharpness, bool bicubic)
+{
+    float4 rgba = bicubic
+        ? SampleBicubic(uv)
+        : g_SourceTexture.SampleLevel(SS_ClampLinear, uv, 0);
+
+    if (sharpness <= 0.0) {
+        return rgba;
+    }
+
+    float2 dx = float2(g_texSizeReciprocal.x, 0.0);
+    float2 dy = float2(0.0, g_texSizeReciprocal.y);
+
+    float3 n = g_SourceTexture.SampleLevel(SS_ClampLinear, uv - dy, 0).rgb;
+    float3 w = g_SourceTexture.SampleLevel(SS_ClampLinear, uv - dx, 0).rgb;
+    float3 e = g_SourceTexture.SampleLevel(SS_ClampLinear, min(uv + dx, texEdge), 0).rgb;
+    float3 s = g_SourceTexture.SampleLevel(SS_ClampLinear, min(uv + dy, texEdge), 0).rgb;
+
+    float3 mn = min(rgba.rgb, min(min(n, w), min(e, s)));
+    float3 mx = max(rgba.rgb, max(max(n, w), max(e, s)));
+
+    // Sharpen less where the local contrast is already high.
+    float3 amp = sqrt(saturate(min(mn, 1.0 - mx) / max(mx, 1e-5)));
+    float3 peak = -amp / lerp(8.0, 5.0, sharpness);
+
+    rgba.rgb = saturate((rgba.rgb + (n + w + e + s) * peak) / (1.0 + 4.0 * peak));
+    return rgba;
+}
+
+// Comfort vignette, narrowing the view as the camera turns faster.
+float TunnelVision(float2 uv)
+{
+    float intensity = ErfpsFlagUnorm(ERFPS_TUNNEL_VISION);
+
+    float r = length((uv - 0.5) * float2(g_vCameraParam.x, 1.0));
+    float inner = lerp(1.0, 0.25, intensity);
+
+    return 1.0 - smoothstep(inner, inner + 0.35, r) * intensity;
+}
+
+// Triangles at the edge of the screen, pointing towards threats outside the view.
+float ThreatMarkerAlpha(float2 uv)
+{
+    float2 c = (uv - 0.5) * float2(g_vCameraParam.x, 1.0);
+    float angle = atan2(c.x, -c.y);
+
+    // Position along the markers, from the base (0.0) to the tip (1.0).
+    float t = (length(uv * 2.0 - 1.0) - 0.82) / 0.12;
+    float extent = saturate(t * 20.0) * saturate((1.0 - t) * 20.0);
+
+    float alpha = 0.0;
+
+    [unroll]
+    for (uint i = 0; i < ERFPS_MAX_MARKERS; i++) {
+        float2 marker = ErfpsMarker(g_ErfpsMarkers.x, i);
+        float delta = AngleDelta(angle, marker.x);
+
+        float halfWidth = 0.06 * (1.0 - t);
+        float width = saturate((halfWidth - delta) / 0.005);
+
+        alpha = max(alpha, width * marker.y);
     }
+
+    return alpha * extent;
 }
 
 float4 PSMain(float4 position : SV_Position, float3 coord : TEXCOORD) : SV_TARGET
 {
     float2 xy = coord.xy;
 
-    float crosshairAlpha = CrosshairAlpha(xy);
-    if (crosshairAlpha > 0.001) {
+    // Ignore a payload forwarded by a mismatched version of erfps2.
+    bool erfps = ErfpsFlag(ERFPS_VERSION) == ERFPS_PAYLOAD_VERSION;
+
+    float2 crosshairAlpha = erfps ? CrosshairCoverage(xy) : 0.0;
+    if (crosshairAlpha.x + crosshairAlpha.y > 0.001) {
         // Draw crosshair.
         float4 rgba = g_SourceTexture.SampleLevel(SS_ClampLinear, xy, 0);
-        rgba.rgb = lerp(rgba.rgb, 1.0 - rgba.rgb, crosshairAlpha);
+        rgba.rgb = CrosshairBlend(rgba.rgb, crosshairAlpha);
         return rgba;
     }
 
-    if (g_ErfpsFlags & 1) {
+    float sharpness = 0.0;
+    bool bicubic = false;
+
+    if (erfps && ErfpsFlag(ERFPS_FOV_CORRECTION) != 0) {
         // Apply FOV correction.
-        if (g_ErfpsFlags & 2) {
-            xy = MapUvBarrel(xy);
-        } else {
-            xy = MapUvFisheye(xy);
+        float2 uv = xy;
+        xy = MapUv(uv);
+
+        float fovSharpening = ErfpsFlagUnorm(ERFPS_FOV_SHARPENING);
+        if (fovSharpening > 0.0) {
+            // Full sharpening from 1.25x magnification.
+            sharpness = fovSharpening * saturate((Magnification(uv, xy) - 1.0) * 4.0);
         }
+
+        bicubic = ErfpsFlag(ERFPS_FOV_BICUBIC) != 0;
     }
 
     float2 xy2m1 = xy * 2.0 - 1.0;
@@ -173,15 +564,25 @@ float4 PSMain(float4 position : SV_Position, float3 coord : TEXCOORD) : SV_TARGE
 
     float2 xy2m1ChromaR = xy2m1 * chromaR + xy;
     float2 rCoord = min(xy2m1ChromaR * dynamicScreenPercentage, texEdge);
-    float r = g_SourceTexture.SampleLevel(SS_ClampLinear, rCoord, 0).r;
+    float r = SampleScene(rCoord, texEdge, sharpness, bicubic).r;
 
     float2 xy2m1ChromaG = xy2m1 * chromaG + xy;
     float2 gCoord = min(xy2m1ChromaG * dynamicScreenPercentage, texEdge);
-    float g = g_SourceTexture.SampleLevel(SS_ClampLinear, gCoord, 0).g;
+    float g = SampleScene(gCoord, texEdge, sharpness, bicubic).g;
     
     float2 xy2m1ChromaB = xy2m1 * chromaB + xy;
     float2 bCoord = min(xy2m1ChromaB * dynamicScreenPercentage, texEdge);
-    float b = g_SourceTexture.SampleLevel(SS_ClampLinear, bCoord, 0).b;
+    float b = SampleScene(bCoord, texEdge, sharpness, bicubic).b;
+
+    float3 rgb = float3(r, g, b);
+
+    if (erfps && ErfpsFlag(ERFPS_TUNNEL_VISION) != 0) {
+        rgb *= TunnelVision(coord.xy);
+    }
+
+    if (erfps && g_ErfpsMarkers.x != 0) {
+        rgb = lerp(rgb, float3(1.0, 0.75, 0.2), ThreatMarkerAlpha(coord.xy));
+    }
 
-    return float4(r, g, b, 1.0);
+    return float4(rgb, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+use eldenring::cs::{ChrIns, FieldInsHandle, WorldChrMan};
+use fromsoftware_shared::{F32ModelMatrix, F32Vector4, FromStatic};
+use glam::{Vec3, Vec4};
+
+use crate::{program::Program, rva::GET_DMY_POS_RVA};
+
+pub trait ChrInsExt {
+    /// Dummy poly the game uses as the lock on point.
+    const LOCK_ON_DMY_ID: u32 = 220;
+
+    /// Dummy poly on the head of the player model, which other models may not have.
+    const HEAD_DMY_ID: u32 = 907;
+
+    unsafe fn from_handle<'a>(handle: FieldInsHandle) -> Option<&'a mut Self>;
+
+    fn position(&self) -> Vec3;
+
+    fn is_alive(&self) -> bool;
+
+    /// World space matrix of a dummy poly, or `None` if the model doesn't have it.
+    fn dmy_poly_matrix(&self, dmy_id: u32) -> Option<F32ModelMatrix>;
+
+    fn dmy_poly_position(&self, dmy_id: u32) -> Option<Vec3> {
+        self.dmy_poly_matrix(dmy_id)
+            .map(|matrix| Vec4::from(matrix.3).truncate())
+    }
+}
+
+/// Characters loaded in the world, including the players.
+pub fn world_characters<'a>() -> impl Iterator<Item = &'a mut ChrIns> {
+    let world_chr_man = unsafe { WorldChrMan::instance().ok() };
+
+    world_chr_man
+        .into_iter()
+        .flat_map(|world_chr_man| world_chr_man.chr_sets.iter())
+        .flatten()
+        .flat_map(|chr_set| unsafe { chr_set.as_ref() }.characters())
+}
+
+impl ChrInsExt for ChrIns {
+    unsafe fn from_handle<'a>(handle: FieldInsHandle) -> Option<&'a mut Self> {
+        let world_chr_man = unsafe { WorldChrMan::instance().ok()? };
+        world_chr_man.chr_ins_by_handle(&handle)
+    }
+
+    fn position(&self) -> Vec3 {
+        Vec4::from(self.chr_ctrl.model_matrix.3).truncate()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.module_container.data.hp > 0
+    }
+
+    fn dmy_poly_matrix(&self, dmy_id: u32) -> Option<F32ModelMatrix> {
+        type GetDmyPos = unsafe extern "C" fn(
+            *const ChrIns,
+            *mut F32ModelMatrix,
+            *const u32,
+            i32,
+        ) -> *mut F32Vector4;
+
+        // Fetch a model matrix for the dummy poly in world space.
+        unsafe {
+            let get_dmy_pos = Program::current().derva_ptr::<GetDmyPos>(GET_DMY_POS_RVA);
+
+            let mut dmy_pos = F32ModelMatrix::IDENTITY;
+            get_dmy_pos(self, &mut dmy_pos, &dmy_id, 1);
+
+            // A failed lookup leaves the matrix untouched, at the world origin.
+            (Vec4::from(dmy_pos.3) != Vec4::W).then_some(dmy_pos)
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::sync::LazyLock;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
 
 use serde::Deserialize;
 
@@ -12,6 +15,8 @@ pub mod updater;
 pub struct Config {
     pub fov: f32,
 
+    pub hor_plus_fov: bool,
+
     pub angle_limit: [f32; 2],
 
     pub extra_player_height: f32,
@@ -24,9 +29,29 @@ pub struct Config {
 
     pub prioritize_lock_on: bool,
 
+    pub lock_on_aim_point: LockOnAimPoint,
+
+    pub lock_on_aim_height: f32,
+
+    pub lock_chase_rate: (f32, f32),
+
+    pub lock_chase_curve: ChaseRateCurve,
+
+    pub lock_chase_ramp_time: f32,
+
+    pub lock_on_dead_zone: f32,
+
+    pub reacquire_target: bool,
+
+    pub reacquire_grace_period: f32,
+
+    pub reacquire_angle: f32,
+
+    pub free_look_ease_time: f32,
+
     pub unlocked_movement: bool,
 
-    pub unobtrusive_dodges: bool,
+    pub unobtrusive_dodges: Option<DodgeTransparency>,
 
     pub track_dodges: bool,
 
@@ -34,23 +59,59 @@ pub struct Config {
 
     pub restricted_sprint: bool,
 
+    pub aim_convergence: bool,
+
     pub use_stabilizer: bool,
 
     pub stabilizer_window: f32,
 
     pub stabilizer_factor: f32,
 
+    pub comfort: Comfort,
+
     pub crosshair: CrosshairKind,
 
     pub crosshair_scale: (f32, f32),
 
+    pub crosshair_style: CrosshairStyle,
+
+    pub crosshair_shape: CrosshairShape,
+
+    pub crosshair_feedback: CrosshairFeedback,
+
+    pub minimal_hud: Option<MinimalHud>,
+
+    pub threat_indicator_radius: Option<f32>,
+
     pub use_fov_correction: bool,
 
-    pub use_barrel_correction: bool,
+    pub fov_projection: FovProjection,
 
     pub correction_strength: f32,
 
     pub correction_cylindricity: f32,
+
+    pub correction_sharpening: f32,
+
+    pub bicubic_resampling: bool,
+
+    pub hud_correction: HudCorrection,
+
+    pub chromatic_aberration: PerspectiveScale,
+
+    pub posture_offsets: PostureOffsets,
+
+    pub weapon_overrides: WeaponOverrides,
+
+    pub hidden_body_parts: BodyParts,
+
+    pub body_awareness: Option<BodyAwareness>,
+
+    pub protector_overrides: HashMap<u32, BodyParts>,
+
+    pub sheathed_weapons: SheathedWeapons,
+
+    pub proximity_hide: Option<ProximityHide>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Deserialize)]
@@ -62,6 +123,454 @@ pub enum CrosshairKind {
     Circle,
     CircleDot,
     Angled,
+    Custom,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FovProjection {
+    Fisheye,
+    Barrel,
+    Panini,
+    Stereographic,
+    Cylindrical,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrosshairBlend {
+    #[default]
+    Invert,
+    Solid,
+    SolidOutline,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CrosshairStyle {
+    pub color: [f32; 3],
+    pub opacity: f32,
+    pub outline_color: [f32; 3],
+    pub blend: CrosshairBlend,
+}
+
+/// Shape of [`CrosshairKind::Custom`], with sizes in whole pixels at 1080p.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CrosshairShape {
+    pub dot_radius: u32,
+    pub line_length: u32,
+    pub line_gap: u32,
+    pub line_thickness: u32,
+    pub t_style: bool,
+    pub ring_radius: u32,
+    pub outline_width: u32,
+    /// Rotation in eighths of a turn clockwise.
+    pub rotation: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CrosshairFeedback {
+    pub highlight_targets: bool,
+    pub target_color: [f32; 3],
+    pub target_scale: f32,
+    pub fade_delay: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Comfort {
+    pub tunnel_vision: f32,
+    pub horizon_lock: f32,
+    /// Maximum vertical head bob, in meters.
+    pub max_head_bob: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MinimalHud {
+    /// Time out of combat before the HUD hides, in seconds.
+    pub hide_delay: f32,
+    pub fade_out_time: f32,
+    pub fade_in_time: f32,
+    /// Virtual key code of the key that shows the HUD while held.
+    pub show_key: Option<u16>,
+    /// Controller button that shows the HUD while held.
+    pub show_button: Option<GamepadButton>,
+    pub hide_crosshair: bool,
+}
+
+/// A button of an XInput controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadButton {
+    None,
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Start,
+    LeftShoulder,
+    RightShoulder,
+    LeftThumb,
+    RightThumb,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+/// World-anchored HUD elements, drawn by `CSFeManImp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HudElement {
+    LockOn,
+    EnemyTags,
+    FriendlyTags,
+}
+
+/// Which HUD elements are moved to match FOV correction.
+///
+/// These are the elements `CSFeManImp` projects from the world to the screen, the rest of
+/// its HUD has fixed screen positions. World markers drawn in the scene, like messages and
+/// summon signs, are corrected by the shader along with it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HudCorrection {
+    pub lock_on: bool,
+    /// `CSFeManImp::enemy_chr_tag_displays`.
+    pub enemy_tags: bool,
+    /// `CSFeManImp::friendly_chr_tag_displays`.
+    pub friendly_tags: bool,
+}
+
+impl Default for HudCorrection {
+    fn default() -> Self {
+        Self {
+            lock_on: true,
+            enemy_tags: true,
+            friendly_tags: true,
+        }
+    }
+}
+
+/// Transparency of the player during dodges in first person.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DodgeTransparency {
+    pub opacity: f32,
+    pub fade_in_time: f32,
+    pub fade_out_time: f32,
+}
+
+/// A scale applied separately in first person and third person.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PerspectiveScale {
+    pub first_person: f32,
+    pub third_person: f32,
+}
+
+impl PerspectiveScale {
+    pub fn get(&self, first_person: bool) -> f32 {
+        match first_person {
+            true => self.first_person,
+            false => self.third_person,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockOnAimPoint {
+    LockPoint,
+    Head,
+    Chest,
+    Height,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChaseRateCurve {
+    Linear,
+    EaseIn,
+    EaseOut,
+    Smooth,
+}
+
+impl ChaseRateCurve {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::Smooth => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Parts and model masks of the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BodyParts {
+    /// Bits of `ChrAsmModelIns::parts_model_ins` slots.
+    pub parts: u64,
+    /// Bits of the model masks starting at [`BodyParts::MODEL_MASK_OFFSET`].
+    pub model_masks: [u64; 3],
+}
+
+impl BodyParts {
+    /// Offset of the model masks in `ChrAsmModelRes`.
+    pub const MODEL_MASK_OFFSET: usize = 0x48;
+
+    /// Slots of the weapon and sheath parts, see `PlayerExt::enable_sheathed_weapons`.
+    const WEAPON_SLOTS: [usize; 4] = [7, 8, 11, 12];
+
+    fn new(parts: &[usize], model_masks: &[usize]) -> Self {
+        let mut body_parts = Self::default();
+
+        for &slot in parts {
+            if slot < 64 && !Self::WEAPON_SLOTS.contains(&slot) {
+                body_parts.parts |= 1 << slot;
+            }
+        }
+
+        for &bit in model_masks {
+            if let Some(mask) = body_parts.model_masks.get_mut(bit / 64) {
+                *mask |= 1 << (bit % 64);
+            }
+        }
+
+        body_parts
+    }
+
+    pub fn has_part(&self, slot: usize) -> bool {
+        slot < 64 && self.parts & (1 << slot) != 0
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            parts: self.parts | other.parts,
+            model_masks: [0, 1, 2].map(|i| self.model_masks[i] | other.model_masks[i]),
+        }
+    }
+}
+
+/// Hiding the upper torso while looking down in first person.
+///
+/// The torso parts are hidden with the display flag of the parts, like the face. Fading them
+/// is not possible, the player's `base_transparency` applies to the whole model.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BodyAwareness {
+    pub torso_parts: BodyParts,
+    /// Camera pitch below which the torso is hidden, in radians (negative is down).
+    pub torso_pitch: f32,
+}
+
+/// Visibility of the weapons and sheaths in first person.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SheathedWeapons {
+    /// Weapons that use the sheath model slot for part of the weapon.
+    pub sheath_ids: HashSet<u32>,
+    pub sheath_categories: HashSet<WeaponCategory>,
+    pub dual_blade_sheaths: bool,
+    /// Visibility of the left and right hand weapons.
+    pub one_handed: [bool; 2],
+    pub left_both_hands: [bool; 2],
+    pub right_both_hands: [bool; 2],
+    pub mounted: [bool; 2],
+}
+
+impl SheathedWeapons {
+    /// Whether the sheath of a weapon stays visible.
+    pub fn has_sheath(
+        &self,
+        id: u32,
+        category: Option<WeaponCategory>,
+        is_dual_blade: bool,
+    ) -> bool {
+        self.sheath_ids.contains(&id)
+            || category.is_some_and(|category| self.sheath_categories.contains(&category))
+            || self.dual_blade_sheaths && is_dual_blade
+    }
+}
+
+/// Hiding parts of the player that come close to the camera in first person.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProximityHide {
+    pub radius: f32,
+    pub weapons: bool,
+    pub shield: bool,
+    pub arm_parts: BodyParts,
+    /// Dummy polys along the left arm and weapon, the closest one is measured.
+    pub left_dmy_polys: Vec<u32>,
+    /// Dummy polys along the right arm and weapon, the closest one is measured.
+    pub right_dmy_polys: Vec<u32>,
+}
+
+/// How the player holds their weapons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandLayout {
+    OneHanded,
+    TwoHanded,
+    /// Dual blades, or weapons of the same `wep_type` in both hands.
+    DualWield,
+}
+
+/// Angles added to the left and right hand posture in first person, by [`HandLayout`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PostureOffsets {
+    pub one_handed: [i32; 2],
+    pub two_handed: [i32; 2],
+    pub dual_wield: [i32; 2],
+}
+
+impl PostureOffsets {
+    pub fn get(&self, layout: HandLayout) -> [i32; 2] {
+        match layout {
+            HandLayout::OneHanded => self.one_handed,
+            HandLayout::TwoHanded => self.two_handed,
+            HandLayout::DualWield => self.dual_wield,
+        }
+    }
+}
+
+/// Settings that replace the ones above while a weapon is held.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeaponOverride {
+    pub crosshair: Option<CrosshairKind>,
+    pub crosshair_scale_x: Option<f32>,
+    pub crosshair_scale_y: Option<f32>,
+    pub fov: Option<f32>,
+    pub unlocked_movement: Option<bool>,
+    /// Angles added to the left and right hand posture.
+    pub posture_offset: Option<[i32; 2]>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WeaponOverrides {
+    by_id: HashMap<u32, WeaponOverride>,
+    by_category: HashMap<WeaponCategory, WeaponOverride>,
+}
+
+/// Weapon categories by `EQUIP_PARAM_WEAPON_ST::wep_type`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeaponCategory {
+    Dagger,
+    StraightSword,
+    Greatsword,
+    ColossalSword,
+    CurvedSword,
+    CurvedGreatsword,
+    Katana,
+    Twinblade,
+    ThrustingSword,
+    HeavyThrustingSword,
+    Axe,
+    Greataxe,
+    Hammer,
+    GreatHammer,
+    Flail,
+    Spear,
+    GreatSpear,
+    Halberd,
+    Reaper,
+    Fist,
+    Claw,
+    Whip,
+    ColossalWeapon,
+    LightBow,
+    Bow,
+    Greatbow,
+    Crossbow,
+    Ballista,
+    GlintstoneStaff,
+    SacredSeal,
+    SmallShield,
+    MediumShield,
+    Greatshield,
+    Torch,
+    HandToHand,
+    PerfumeBottle,
+    ThrustingShield,
+    ThrowingBlade,
+    BackhandBlade,
+    LightGreatsword,
+    GreatKatana,
+    BeastClaw,
+}
+
+impl WeaponOverrides {
+    /// Finds the override for a weapon, preferring its id over its category.
+    pub fn get(&self, id: u32, category: Option<WeaponCategory>) -> Option<&WeaponOverride> {
+        self.by_id
+            .get(&id)
+            .or_else(|| self.by_category.get(&category?))
+    }
+}
+
+impl Config {
+    /// Body parts hidden in first person while wearing the protectors.
+    pub fn hidden_body_parts(&self, protector_ids: [u32; 4]) -> BodyParts {
+        protector_ids
+            .iter()
+            .filter_map(|id| self.protector_overrides.get(id))
+            .fold(self.hidden_body_parts, |hidden, &parts| hidden.union(parts))
+    }
+}
+
+impl WeaponCategory {
+    pub fn is_shield(self) -> bool {
+        matches!(
+            self,
+            Self::SmallShield | Self::MediumShield | Self::Greatshield | Self::ThrustingShield
+        )
+    }
+
+    pub fn from_wep_type(wep_type: u16) -> Option<Self> {
+        let category = match wep_type {
+            1 => Self::Dagger,
+            3 => Self::StraightSword,
+            5 => Self::Greatsword,
+            7 => Self::ColossalSword,
+            9 => Self::CurvedSword,
+            11 => Self::CurvedGreatsword,
+            13 => Self::Katana,
+            14 => Self::Twinblade,
+            15 => Self::ThrustingSword,
+            16 => Self::HeavyThrustingSword,
+            17 => Self::Axe,
+            19 => Self::Greataxe,
+            21 => Self::Hammer,
+            23 => Self::GreatHammer,
+            24 => Self::Flail,
+            25 => Self::Spear,
+            28 => Self::GreatSpear,
+            29 => Self::Halberd,
+            31 => Self::Reaper,
+            35 => Self::Fist,
+            37 => Self::Claw,
+            39 => Self::Whip,
+            41 => Self::ColossalWeapon,
+            50 => Self::LightBow,
+            51 => Self::Bow,
+            53 => Self::Greatbow,
+            55 => Self::Crossbow,
+            56 => Self::Ballista,
+            57 => Self::GlintstoneStaff,
+            61 => Self::SacredSeal,
+            65 => Self::SmallShield,
+            67 => Self::MediumShield,
+            69 => Self::Greatshield,
+            87 => Self::Torch,
+            88 => Self::HandToHand,
+            89 => Self::PerfumeBottle,
+            90 => Self::ThrustingShield,
+            91 => Self::ThrowingBlade,
+            92 => Self::BackhandBlade,
+            93 => Self::LightGreatsword,
+            94 => Self::GreatKatana,
+            95 => Self::BeastClaw,
+            _ => return None,
+        };
+
+        Some(category)
+    }
 }
 
 impl From<toml::Config> for Config {
@@ -77,41 +586,254 @@ impl From<toml::Config> for Config {
         let stabilizer_window = config.stabilizer.smoothing_window.clamp(0.1, 1.0);
         let stabilizer_factor = config.stabilizer.smoothing_factor.clamp(0.0, 1.0);
 
+        let lock_on_aim_height = config.lock_on.aim_height.clamp(0.0, 1.0);
+
+        let lock_chase_rate_max = config.lock_on.chase_rate_max.clamp(0.0, 1.0);
+        let lock_chase_rate_min = config
+            .lock_on
+            .chase_rate_min
+            .clamp(0.0, lock_chase_rate_max);
+        let lock_chase_ramp_time = config.lock_on.chase_rate_ramp_time.clamp(0.01, 5.0);
+
+        let lock_on_dead_zone = config.lock_on.dead_zone.clamp(0.0, 15.0).to_radians();
+
+        let reacquire_grace_period = config.lock_on.reacquire_grace_period.clamp(0.0, 3.0);
+        let reacquire_angle = config.lock_on.reacquire_angle.clamp(0.0, 90.0).to_radians();
+        let free_look_ease_time = config.lock_on.free_look_ease_time.clamp(0.0, 2.0);
+
         let crosshair_scale_x = config.crosshair.scale_x.clamp(0.1, 4.0);
         let crosshair_scale_y = config.crosshair.scale_y.clamp(0.1, 4.0);
 
+        let crosshair_style = CrosshairStyle {
+            color: config.crosshair.color.map(|c| c.clamp(0.0, 1.0)),
+            opacity: config.crosshair.opacity.clamp(0.0, 1.0),
+            outline_color: config.crosshair.outline_color.map(|c| c.clamp(0.0, 1.0)),
+            blend: config.crosshair.blend,
+        };
+
+        let crosshair_shape = {
+            let custom = &config.crosshair.custom;
+
+            CrosshairShape {
+                dot_radius: custom.dot_radius.min(3),
+                line_length: custom.line_length.min(15),
+                line_gap: custom.line_gap.min(15),
+                line_thickness: custom.line_thickness.clamp(1, 4),
+                t_style: custom.t_style,
+                ring_radius: custom.ring_radius.min(15),
+                outline_width: custom.outline_width.min(3),
+                rotation: (custom.rotation / 45.0).round().rem_euclid(8.0) as u32,
+            }
+        };
+
+        let crosshair_feedback = CrosshairFeedback {
+            highlight_targets: config.crosshair.highlight_targets,
+            target_color: config.crosshair.target_color.map(|c| c.clamp(0.0, 1.0)),
+            target_scale: config.crosshair.target_scale.clamp(0.5, 2.0),
+            fade_delay: Some(config.crosshair.fade_out_of_combat.clamp(0.0, 60.0))
+                .filter(|&delay| delay > 0.0),
+        };
+
+        let head_bob_cap = config.comfort.head_bob_cap.clamp(0.0, 1.0);
+
+        let comfort = Comfort {
+            tunnel_vision: config.comfort.tunnel_vision.clamp(0.0, 1.0),
+            horizon_lock: config.comfort.horizon_lock.clamp(0.0, 1.0),
+            max_head_bob: (head_bob_cap > 0.0).then_some((1.0 - head_bob_cap) * 0.1),
+        };
+
         let correction_strength = config.fov.fov_correction_strength.clamp(0.0, 1.0);
         let correction_cylindricity =
             config.fov.fov_correction_cylindricity.clamp(0.0, 1.0) * 1.5 + 0.5;
 
-        let (use_fov_correction, use_barrel_correction) = match config.fov.fov_correction {
-            toml::FovCorrection::None => (false, false),
-            toml::FovCorrection::Fisheye => (true, false),
-            toml::FovCorrection::Barrel => (true, true),
+        let mut hud_correction = HudCorrection::default();
+
+        for element in &config.fov.uncorrected_hud_elements {
+            match element {
+                HudElement::LockOn => hud_correction.lock_on = false,
+                HudElement::EnemyTags => hud_correction.enemy_tags = false,
+                HudElement::FriendlyTags => hud_correction.friendly_tags = false,
+            }
+        }
+
+        let mut weapon_overrides = WeaponOverrides::default();
+
+        for weapon in &config.weapon {
+            let weapon_override = WeaponOverride {
+                crosshair: weapon.crosshair,
+                crosshair_scale_x: weapon.crosshair_scale_x.map(|scale| scale.clamp(0.1, 4.0)),
+                crosshair_scale_y: weapon.crosshair_scale_y.map(|scale| scale.clamp(0.1, 4.0)),
+                fov: weapon
+                    .horizontal_fov
+                    .map(|degrees| degrees.clamp(45.0, 130.0).to_radians()),
+                unlocked_movement: weapon.unlocked_movement,
+                posture_offset: weapon
+                    .posture_offset
+                    .map(|angles| angles.map(|angle| angle.clamp(-90, 90))),
+            };
+
+            if let Some(id) = weapon.id {
+                weapon_overrides.by_id.insert(id, weapon_override);
+            }
+
+            if let Some(category) = weapon.category {
+                weapon_overrides
+                    .by_category
+                    .insert(category, weapon_override);
+            }
+        }
+
+        let hidden_body_parts =
+            BodyParts::new(&config.body.hidden_parts, &config.body.hidden_model_masks);
+
+        let torso_parts = BodyParts::new(&config.body.torso_parts, &config.body.torso_model_masks);
+
+        if config.body.full_body_awareness && torso_parts == BodyParts::default() {
+            log::warn!(
+                "body.full_body_awareness is off, no torso_parts or torso_model_masks are set"
+            );
+        }
+
+        let body_awareness = (config.body.full_body_awareness
+            && torso_parts != BodyParts::default())
+        .then(|| BodyAwareness {
+            torso_parts,
+            torso_pitch: -config.body.torso_pitch.clamp(0.0, 80.0).to_radians(),
+        });
+
+        let protector_overrides = config
+            .protector
+            .iter()
+            .map(|protector| {
+                let parts = BodyParts::new(&protector.hidden_parts, &protector.hidden_model_masks);
+
+                (protector.id, parts)
+            })
+            .collect();
+
+        let sheathed_weapons = SheathedWeapons {
+            sheath_ids: config.sheathed_weapons.sheath_ids.iter().copied().collect(),
+            sheath_categories: config
+                .sheathed_weapons
+                .sheath_categories
+                .iter()
+                .copied()
+                .collect(),
+            dual_blade_sheaths: config.sheathed_weapons.dual_blade_sheaths,
+            one_handed: config.sheathed_weapons.one_handed,
+            left_both_hands: config.sheathed_weapons.left_both_hands,
+            right_both_hands: config.sheathed_weapons.right_both_hands,
+            mounted: config.sheathed_weapons.mounted,
+        };
+
+        let has_dmy_polys = !(config.proximity_hide.left_dmy_polys.is_empty()
+            && config.proximity_hide.right_dmy_polys.is_empty());
+
+        if config.proximity_hide.enabled && !has_dmy_polys {
+            log::warn!("proximity_hide is off, no left_dmy_polys or right_dmy_polys are set");
+        }
+
+        let proximity_hide =
+            (config.proximity_hide.enabled && has_dmy_polys).then(|| ProximityHide {
+                radius: config.proximity_hide.radius.clamp(0.1, 1.5),
+                weapons: config.proximity_hide.weapons,
+                shield: config.proximity_hide.shield,
+                arm_parts: BodyParts::new(&config.proximity_hide.arm_parts, &[]),
+                left_dmy_polys: config.proximity_hide.left_dmy_polys.clone(),
+                right_dmy_polys: config.proximity_hide.right_dmy_polys.clone(),
+            });
+
+        let (use_fov_correction, fov_projection) = match config.fov.fov_correction {
+            toml::FovCorrection::None => (false, FovProjection::Fisheye),
+            toml::FovCorrection::Fisheye => (true, FovProjection::Fisheye),
+            toml::FovCorrection::Barrel => (true, FovProjection::Barrel),
+            toml::FovCorrection::Panini => (true, FovProjection::Panini),
+            toml::FovCorrection::Stereographic => (true, FovProjection::Stereographic),
+            toml::FovCorrection::Cylindrical => (true, FovProjection::Cylindrical),
         };
 
         Self {
             fov,
+            hor_plus_fov: config.fov.hor_plus,
             angle_limit: const { [f32::to_radians(-80.0), f32::to_radians(70.0)] },
             extra_player_height,
             start_in_first_person: config.gameplay.start_in_first_person,
             show_tutorial: config.gameplay.show_tutorial,
             prioritize_lock_on: config.gameplay.prioritize_lock_on,
             soft_lock_on: config.gameplay.soft_lock_on,
+            lock_on_aim_point: config.lock_on.aim_point,
+            lock_on_aim_height,
+            lock_chase_rate: (lock_chase_rate_min, lock_chase_rate_max),
+            lock_chase_curve: config.lock_on.chase_rate_curve,
+            lock_chase_ramp_time,
+            lock_on_dead_zone,
+            reacquire_target: config.lock_on.reacquire_target,
+            reacquire_grace_period,
+            reacquire_angle,
+            free_look_ease_time,
             unlocked_movement: config.gameplay.unlocked_movement,
-            unobtrusive_dodges: config.gameplay.unobtrusive_dodges,
+            unobtrusive_dodges: config
+                .gameplay
+                .unobtrusive_dodges
+                .then_some(DodgeTransparency {
+                    opacity: config.gameplay.dodge_opacity.clamp(0.0, 1.0),
+                    fade_in_time: config.gameplay.dodge_fade_in_time.clamp(0.0, 1.0),
+                    fade_out_time: config.gameplay.dodge_fade_out_time.clamp(0.0, 1.0),
+                }),
             track_dodges: config.gameplay.track_dodges,
             track_damage: config.gameplay.track_damage,
             restricted_sprint: config.gameplay.restricted_sprint,
+            aim_convergence: config.gameplay.aim_convergence,
             use_stabilizer: config.stabilizer.enabled,
             stabilizer_window,
             stabilizer_factor,
+            comfort,
             crosshair: config.crosshair.kind,
             crosshair_scale: (crosshair_scale_x, crosshair_scale_y),
+            crosshair_style,
+            crosshair_shape,
+            crosshair_feedback,
+            minimal_hud: config.hud.minimal.then_some(MinimalHud {
+                hide_delay: config.hud.hide_delay.clamp(0.5, 60.0),
+                fade_out_time: config.hud.fade_out_time.clamp(0.0, 5.0),
+                fade_in_time: config.hud.fade_in_time.clamp(0.0, 5.0),
+                show_key: Some(config.hud.show_key).filter(|&key| key != 0),
+                show_button: Some(config.hud.show_button)
+                    .filter(|&button| button != GamepadButton::None),
+                hide_crosshair: config.hud.hide_crosshair,
+            }),
+            threat_indicator_radius: config
+                .threat_indicator
+                .enabled
+                .then_some(config.threat_indicator.radius.clamp(2.0, 50.0)),
             use_fov_correction,
-            use_barrel_correction,
+            fov_projection,
             correction_strength,
             correction_cylindricity,
+            correction_sharpening: config.fov.fov_correction_sharpening.clamp(0.0, 1.0),
+            bicubic_resampling: config.fov.bicubic_resampling,
+            hud_correction,
+            chromatic_aberration: PerspectiveScale {
+                first_person: config
+                    .post
+                    .chromatic_aberration_first_person
+                    .clamp(0.0, 2.0),
+                third_person: config
+                    .post
+                    .chromatic_aberration_third_person
+                    .clamp(0.0, 2.0),
+            },
+            posture_offsets: PostureOffsets {
+                one_handed: config.posture.one_handed.map(|angle| angle.clamp(-90, 90)),
+                two_handed: config.posture.two_handed.map(|angle| angle.clamp(-90, 90)),
+                dual_wield: config.posture.dual_wield.map(|angle| angle.clamp(-90, 90)),
+            },
+            weapon_overrides,
+            hidden_body_parts,
+            body_awareness,
+            protector_overrides,
+            sheathed_weapons,
+            proximity_hide,
         }
     }
 }
@@ -1,22 +1,42 @@
 use serde::Deserialize;
 
-use crate::config::CrosshairKind;
+use crate::config::{
+    ChaseRateCurve, CrosshairBlend, CrosshairKind, GamepadButton, HudElement, LockOnAimPoint,
+    WeaponCategory,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub fov: Fov,
     pub gameplay: Gameplay,
+    pub lock_on: LockOn,
     pub player: Player,
+    pub body: Body,
+    pub sheathed_weapons: SheathedWeapons,
+    pub posture: Posture,
+    pub proximity_hide: ProximityHide,
     pub stabilizer: Stabilizer,
+    pub comfort: Comfort,
     pub crosshair: Crosshair,
+    pub hud: Hud,
+    pub threat_indicator: ThreatIndicator,
+    pub post: Post,
+    #[serde(default)]
+    pub weapon: Vec<Weapon>,
+    #[serde(default)]
+    pub protector: Vec<Protector>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Fov {
     pub horizontal_fov: f32,
+    pub hor_plus: bool,
     pub fov_correction: FovCorrection,
     pub fov_correction_strength: f32,
     pub fov_correction_cylindricity: f32,
+    pub fov_correction_sharpening: f32,
+    pub bicubic_resampling: bool,
+    pub uncorrected_hud_elements: Vec<HudElement>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -27,9 +47,28 @@ pub struct Gameplay {
     pub soft_lock_on: bool,
     pub unlocked_movement: bool,
     pub unobtrusive_dodges: bool,
+    pub dodge_opacity: f32,
+    pub dodge_fade_in_time: f32,
+    pub dodge_fade_out_time: f32,
     pub track_dodges: bool,
     pub track_damage: bool,
     pub restricted_sprint: bool,
+    pub aim_convergence: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LockOn {
+    pub aim_point: LockOnAimPoint,
+    pub aim_height: f32,
+    pub chase_rate_min: f32,
+    pub chase_rate_max: f32,
+    pub chase_rate_curve: ChaseRateCurve,
+    pub chase_rate_ramp_time: f32,
+    pub dead_zone: f32,
+    pub reacquire_target: bool,
+    pub reacquire_grace_period: f32,
+    pub reacquire_angle: f32,
+    pub free_look_ease_time: f32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +76,45 @@ pub struct Player {
     pub height_multiplier: f32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct Body {
+    pub hidden_parts: Vec<usize>,
+    pub hidden_model_masks: Vec<usize>,
+    pub full_body_awareness: bool,
+    pub torso_pitch: f32,
+    pub torso_parts: Vec<usize>,
+    pub torso_model_masks: Vec<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SheathedWeapons {
+    pub sheath_ids: Vec<u32>,
+    pub sheath_categories: Vec<WeaponCategory>,
+    pub dual_blade_sheaths: bool,
+    pub one_handed: [bool; 2],
+    pub left_both_hands: [bool; 2],
+    pub right_both_hands: [bool; 2],
+    pub mounted: [bool; 2],
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Posture {
+    pub one_handed: [i32; 2],
+    pub two_handed: [i32; 2],
+    pub dual_wield: [i32; 2],
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProximityHide {
+    pub enabled: bool,
+    pub radius: f32,
+    pub weapons: bool,
+    pub shield: bool,
+    pub arm_parts: Vec<usize>,
+    pub left_dmy_polys: Vec<u32>,
+    pub right_dmy_polys: Vec<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Stabilizer {
     pub enabled: bool,
@@ -44,11 +122,83 @@ pub struct Stabilizer {
     pub smoothing_factor: f32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct Comfort {
+    pub tunnel_vision: f32,
+    pub horizon_lock: f32,
+    pub head_bob_cap: f32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Crosshair {
     pub kind: CrosshairKind,
     pub scale_x: f32,
     pub scale_y: f32,
+    pub color: [f32; 3],
+    pub opacity: f32,
+    pub outline_color: [f32; 3],
+    pub blend: CrosshairBlend,
+    pub highlight_targets: bool,
+    pub target_color: [f32; 3],
+    pub target_scale: f32,
+    pub fade_out_of_combat: f32,
+    pub custom: CustomCrosshair,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CustomCrosshair {
+    pub dot_radius: u32,
+    pub line_length: u32,
+    pub line_gap: u32,
+    pub line_thickness: u32,
+    pub t_style: bool,
+    pub ring_radius: u32,
+    pub outline_width: u32,
+    pub rotation: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Hud {
+    pub minimal: bool,
+    pub hide_delay: f32,
+    pub fade_out_time: f32,
+    pub fade_in_time: f32,
+    pub show_key: u16,
+    pub show_button: GamepadButton,
+    pub hide_crosshair: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThreatIndicator {
+    pub enabled: bool,
+    pub radius: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Post {
+    pub chromatic_aberration_first_person: f32,
+    pub chromatic_aberration_third_person: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Weapon {
+    pub id: Option<u32>,
+    pub category: Option<WeaponCategory>,
+    pub crosshair: Option<CrosshairKind>,
+    pub crosshair_scale_x: Option<f32>,
+    pub crosshair_scale_y: Option<f32>,
+    pub horizontal_fov: Option<f32>,
+    pub unlocked_movement: Option<bool>,
+    pub posture_offset: Option<[i32; 2]>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Protector {
+    pub id: u32,
+    #[serde(default)]
+    pub hidden_parts: Vec<usize>,
+    #[serde(default)]
+    pub hidden_model_masks: Vec<usize>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Deserialize)]
@@ -57,6 +207,9 @@ pub enum FovCorrection {
     None,
     Fisheye,
     Barrel,
+    Panini,
+    Stereographic,
+    Cylindrical,
 }
 
 const WITH_COMMENTS: &str = include_str!("../../dist/erfps2.toml");
@@ -1,9 +1,8 @@
 use std::{
     f32::consts::PI,
-    ffi::{CStr, c_char},
     mem,
     ops::{Deref, DerefMut},
-    ptr::NonNull,
+    ptr::{self, NonNull},
     sync::{LazyLock, Once, RwLock},
 };
 
@@ -12,23 +11,50 @@ use eldenring::cs::{
     FieldInsHandle, FieldInsType, GameDataMan, LockTgtMan, PlayerIns,
 };
 use fromsoftware_shared::{F32ViewMatrix, FromStatic};
-use glam::{EulerRot, Mat3A, Mat4, Quat, Vec3, Vec4};
+use glam::{EulerRot, Mat3A, Mat4, Quat, Vec2, Vec3, Vec4};
+use windows::Win32::{
+    Foundation::ERROR_SUCCESS,
+    UI::Input::{
+        KeyboardAndMouse::GetAsyncKeyState,
+        XboxController::{
+            XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B, XINPUT_GAMEPAD_BACK, XINPUT_GAMEPAD_DPAD_DOWN,
+            XINPUT_GAMEPAD_DPAD_LEFT, XINPUT_GAMEPAD_DPAD_RIGHT, XINPUT_GAMEPAD_DPAD_UP,
+            XINPUT_GAMEPAD_LEFT_SHOULDER, XINPUT_GAMEPAD_LEFT_THUMB, XINPUT_GAMEPAD_RIGHT_SHOULDER,
+            XINPUT_GAMEPAD_RIGHT_THUMB, XINPUT_GAMEPAD_START, XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y,
+            XINPUT_STATE, XInputGetState,
+        },
+    },
+};
 
 use crate::{
-    config::{Config, CrosshairKind, updater::ConfigUpdater},
+    chr::{ChrInsExt, world_characters},
+    config::{
+        BodyParts, Config, CrosshairKind, GamepadButton, HandLayout, LockOnAimPoint,
+        WeaponCategory, WeaponOverride, updater::ConfigUpdater,
+    },
     core::{
         behavior::{BehaviorStateSet, BehaviorStates},
+        comfort::TunnelVision,
+        crosshair::{CrosshairInput, CrosshairOutput, CrosshairTracker},
+        dodge::DodgeFade,
         frame_cached::FrameCached,
         head_tracker::HeadTracker,
+        hud::{HudInput, HudTracker},
+        lock_on::{self, LockOnTracker},
+        threat::{Threat, threat_markers},
         time::{FrameTime, TransTime},
         world::{FromWorld, Void, World, WorldState},
     },
-    game::GameDataManExt,
+    game::{GameDataManExt, LockTgtManExt},
     player::PlayerExt,
     program::Program,
     raycast::cast_sphere,
     rva::CAM_WALL_RECOVERY_RVA,
-    shaders::{enable_dithering, enable_fov_correction, enable_vfx_fade, set_crosshair},
+    shaders::{
+        enable_dithering, enable_fov_correction, enable_vfx_fade, payload::MAX_MARKERS,
+        set_chromatic_aberration, set_crosshair, set_fov_filtering, set_threat_markers,
+        set_tunnel_vision,
+    },
     tutorial::{TUTORIAL_EVENT_FLAG_ID, show_tutorial},
 };
 
@@ -37,9 +63,15 @@ pub use behavior::BehaviorState;
 pub mod world;
 
 mod behavior;
+mod comfort;
+mod crosshair;
+mod dodge;
 mod frame_cached;
 mod head_tracker;
+mod hud;
+mod lock_on;
 mod stabilizer;
+mod threat;
 mod time;
 
 pub struct CoreLogic {
@@ -60,6 +92,15 @@ pub struct State {
     trans_time: FrameCached<TransTime>,
     head_tracker: FrameCached<HeadTracker>,
     behavior_states: BehaviorStates,
+    lock_on: LockOnTracker,
+    crosshair: CrosshairTracker,
+    tunnel_vision: TunnelVision,
+    dodge_fade: DodgeFade,
+    hud: HudTracker,
+    weapon_override: WeaponOverride,
+    hidden_body_parts: BodyParts,
+    proximity_hidden: BodyParts,
+    torso_hidden: bool,
     saved_angle_limit: Option<f32>,
 }
 
@@ -154,6 +195,7 @@ where
         self.head_tracker.next_frame(frame_time);
 
         self.update_fov_correction();
+        self.update_post_effects();
     }
 
     pub fn update_follow_cam(&mut self, follow_cam: &mut ChrExFollowCam) {
@@ -187,14 +229,33 @@ where
         }
 
         let frame_time = self.frame_time.get(());
-        if let Some(lock_tgt) = self.get::<LockTgtMan>() {
-            let lock_chase_rate = &mut follow_cam.lock_chase_rate;
-
-            if lock_tgt.is_locked_on && *lock_chase_rate <= 1.0 {
-                *lock_chase_rate = f32::min(*lock_chase_rate + frame_time, 1.0);
-            } else if *lock_chase_rate > 0.3 {
-                *lock_chase_rate = f32::max(*lock_chase_rate - frame_time, 0.3);
-            }
+        if let Some(lock_tgt) = self.get::<LockTgtMan>()
+            && let Some(chr_cam) = self.get::<ChrCam>()
+        {
+            let camera_matrix = chr_cam.pers_cam.matrix;
+            let camera_position = Vec4::from(camera_matrix.3).truncate();
+            let camera_direction = Vec4::from(camera_matrix.2).truncate();
+
+            // The camera is not steered by "soft" lock on.
+            let aim_point = if self.config.soft_lock_on {
+                LockOnAimPoint::LockPoint
+            } else {
+                self.config.lock_on_aim_point
+            };
+
+            let target = lock_tgt
+                .locked_on_target()
+                .and_then(|handle| unsafe { ChrIns::from_handle(handle) })
+                .map(|chr| lock_on::Target::new(chr, aim_point, self.config.lock_on_aim_height));
+
+            let config = self.config;
+            follow_cam.lock_chase_rate = self.lock_on.update(
+                config,
+                frame_time,
+                camera_position,
+                camera_direction,
+                target.as_ref(),
+            );
         }
     }
 
@@ -203,12 +264,22 @@ where
     }
 
     pub fn fov(&self) -> f32 {
+        let mut fov = self.weapon_override.fov.unwrap_or(self.config.fov);
+
+        if self.config.hor_plus_fov {
+            // Keep the vertical FOV of a 16:9 screen.
+            const REFERENCE_ASPECT_RATIO: f32 = 16.0 / 9.0;
+            let width_ratio = self.aspect_ratio() / REFERENCE_ASPECT_RATIO;
+
+            fov = (f32::atan(f32::tan(fov * 0.5) * width_ratio) * 2.0).min(f32::to_radians(170.0));
+        }
+
         if self.is_aim_cam()
             && let Some(chr_cam) = self.get::<ChrCam>()
         {
             let aim_cam_fov = chr_cam.aim_cam.fov;
 
-            if aim_cam_fov <= self.config.fov {
+            if aim_cam_fov <= fov {
                 return aim_cam_fov;
             }
 
@@ -216,34 +287,88 @@ where
             const AIM_CAM_HALF_WIDTH: f32 = 0.41143;
             let width_ratio = aim_cam_fov.atan() / AIM_CAM_HALF_WIDTH;
 
-            f32::tan(self.config.fov.atan() * width_ratio)
+            f32::tan(fov.atan() * width_ratio)
         } else {
-            self.config.fov
+            fov
         }
     }
 
+    pub fn unlocked_movement(&self) -> bool {
+        self.weapon_override
+            .unlocked_movement
+            .unwrap_or(self.config.unlocked_movement)
+    }
+
+    /// Angles added to the left and right hand posture.
+    pub fn posture_offsets(&self, layout: HandLayout) -> [i32; 2] {
+        self.weapon_override
+            .posture_offset
+            .unwrap_or(self.config.posture_offsets.get(layout))
+    }
+
+    fn aspect_ratio(&self) -> f32 {
+        self.get::<ChrCam>()
+            .map(|chr_cam| chr_cam.pers_cam.aspect_ratio)
+            .filter(|aspect_ratio| aspect_ratio.is_finite() && *aspect_ratio > 0.0)
+            .unwrap_or(16.0 / 9.0)
+    }
+
     fn update_fov_correction(&self) {
         enable_fov_correction(
             self.first_person && self.config.use_fov_correction,
             self.config.correction_strength,
             self.config.correction_cylindricity,
-            self.config.use_barrel_correction,
+            self.config.fov_projection,
             self.fov(),
+            self.aspect_ratio(),
         );
+
+        set_fov_filtering(
+            self.config.correction_sharpening,
+            self.config.bicubic_resampling,
+        );
+    }
+
+    fn update_post_effects(&self) {
+        set_chromatic_aberration(self.config.chromatic_aberration.get(self.first_person));
     }
 
     fn set_crosshair_if(&self, cond: bool) {
-        let is_hud_enabled = unsafe {
-            GameDataMan::instance().is_some_and(|game_data_man| game_data_man.is_hud_enabled())
-        };
+        // The crosshair fades with the HUD while the minimal HUD has it switched off.
+        let is_hud_enabled = self.hud.is_hiding()
+            || unsafe {
+                GameDataMan::instance().is_some_and(|game_data_man| game_data_man.is_hud_enabled())
+            };
 
         let crosshair = if cond && is_hud_enabled {
-            self.config.crosshair
+            self.weapon_override
+                .crosshair
+                .unwrap_or(self.config.crosshair)
         } else {
             CrosshairKind::None
         };
 
-        set_crosshair(crosshair, self.config.crosshair_scale);
+        let crosshair_scale = (
+            self.weapon_override
+                .crosshair_scale_x
+                .unwrap_or(self.config.crosshair_scale.0),
+            self.weapon_override
+                .crosshair_scale_y
+                .unwrap_or(self.config.crosshair_scale.1),
+        );
+
+        let CrosshairOutput { scale, mut style } =
+            self.crosshair.apply(self.config, crosshair_scale);
+
+        if self
+            .config
+            .minimal_hud
+            .is_some_and(|hud| hud.hide_crosshair)
+        {
+            style.opacity *= self.hud.opacity();
+        }
+
+        set_crosshair(crosshair, scale, &style, &self.config.crosshair_shape);
     }
 
     fn is_aim_cam(&self) -> bool {
@@ -304,6 +429,7 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
 
     pub fn camera_position(&mut self) -> F32ViewMatrix {
         let camera_rotation = Quat::from_mat3a(&self.chr_cam.pers_cam.matrix.rotation());
+        let camera_rotation = self.lock_on.apply(camera_rotation);
 
         let tracker_args = (&*self).into();
         let output = self.head_tracker.get(tracker_args);
@@ -311,7 +437,15 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
         let head_rotation = output.head_matrix.rotation::<Mat3A>();
         let mut head_position = output.stabilized_head_position;
 
-        let camera_rotation = camera_rotation * output.tracking_rotation;
+        let mut camera_rotation = camera_rotation * output.tracking_rotation;
+
+        let horizon_lock = self.config.comfort.horizon_lock;
+
+        if horizon_lock > 0.0 {
+            let (yaw, pitch, roll) = camera_rotation.to_euler(EulerRot::YXZ);
+            camera_rotation =
+                Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll * (1.0 - horizon_lock));
+        }
 
         let cam_pitch = camera_rotation.to_euler(EulerRot::ZXY).1;
         let cam_pitch_exp = (cam_pitch.abs() / 3.0).powi(2);
@@ -344,8 +478,11 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
         let camera_pos = self.camera_position();
 
         if self.config.soft_lock_on || !self.lock_tgt.is_locked_on {
-            let lock_on_pos =
-                Vec4::from(camera_pos.3) + Vec4::from(self.chr_cam.pers_cam.matrix.2) * 10.0;
+            let lock_on_pos = if self.config.aim_convergence {
+                self.aim_convergence_point(camera_pos).extend(1.0)
+            } else {
+                Vec4::from(camera_pos.3) + Vec4::from(self.chr_cam.pers_cam.matrix.2) * 10.0
+            };
             self.player.set_lock_on_target_position(lock_on_pos);
         }
 
@@ -362,9 +499,32 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
         self.chr_cam.pers_cam.fov = fov;
     }
 
+    /// Casts a ray from the camera, returning the point under the crosshair or a far fallback.
+    fn aim_convergence_point(&self, camera_pos: F32ViewMatrix) -> Vec3 {
+        const MAX_DISTANCE: f32 = 100.0;
+
+        let origin = Vec4::from(camera_pos.3).truncate();
+        let direction = Vec4::from(self.chr_cam.pers_cam.matrix.2).truncate() * MAX_DISTANCE;
+
+        let player = &raw const self.player.chr_ins;
+
+        let hit = cast_sphere(origin, direction, 0.05, 0x2000058, |hit| {
+            hit.field_ins()
+                .is_none_or(|owner| !ptr::addr_eq(owner.as_ptr(), player))
+        });
+
+        hit.map_or(origin + direction, |hit| Vec3::from(hit.pos))
+    }
+
     pub fn update_chr_cam(&mut self) {
         let first_person = self.first_person();
 
+        if first_person {
+            self.update_crosshair();
+        }
+
+        self.update_hud(first_person);
+
         self.set_crosshair_if(
             first_person
                 && (!self.lock_tgt.is_locked_on || self.config.soft_lock_on)
@@ -372,19 +532,33 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
         );
 
         if !first_person {
+            self.tunnel_vision.reset();
+            set_tunnel_vision(0.0);
+
+            set_threat_markers([0.0; MAX_MARKERS], [0.0; MAX_MARKERS]);
+
             return;
         }
 
-        self.player.enable_face_model(false);
-        self.player.enable_sheathed_weapons(false);
+        let config = self.config;
 
-        if self.config.unobtrusive_dodges {
-            let is_dodging = self.has_state(BehaviorState::Evasion);
-            self.player.make_transparent(is_dodging);
-        }
+        self.update_body_parts();
+        self.player
+            .enable_sheathed_weapons(false, &config.sheathed_weapons);
+
+        self.update_dodge_fade();
+
+        // `update_cs_cam` recomputes the camera later in the frame, only this one is remembered.
+        let camera_rotation = Quat::from_mat3a(&self.chr_cam.pers_cam.matrix.rotation());
+        self.lock_on.remember_rotation(camera_rotation);
 
         let camera_pos = self.camera_position();
 
+        self.update_proximity_hide(camera_pos);
+
+        self.update_tunnel_vision(camera_pos.rotation());
+        self.update_threat_markers(camera_pos);
+
         if self.config.restricted_sprint {
             self.restrict_sprint(camera_pos.rotation());
         }
@@ -416,6 +590,204 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
         self.player.chr_ctrl.scale_size_y = player_scale;
     }
 
+    fn update_tunnel_vision(&mut self, camera_rotation: Mat3A) {
+        let frame_time = self.frame_time.get(());
+        let strength = self.config.comfort.tunnel_vision;
+
+        let intensity = match strength > 0.0 {
+            true => {
+                let rotation = Quat::from_mat3a(&camera_rotation);
+                self.tunnel_vision.update(frame_time, rotation) * strength
+            }
+            false => 0.0,
+        };
+
+        set_tunnel_vision(intensity);
+    }
+
+    /// Hides the parts of the player close to the camera.
+    fn update_proximity_hide(&mut self, camera_pos: F32ViewMatrix) {
+        // Weapon slots, see `PlayerExt::enable_sheathed_weapons`.
+        const LEFT_WEAPON_SLOT: u64 = 1 << 7;
+        const RIGHT_WEAPON_SLOT: u64 = 1 << 11;
+
+        let mut hidden = BodyParts::default();
+
+        if let Some(proximity_hide) = &self.config.proximity_hide {
+            let camera_position = Vec4::from(camera_pos.3).truncate();
+            let is_near = |dmy_ids: &[u32]| {
+                dmy_ids.iter().any(|&dmy_id| {
+                    self.player
+                        .dmy_poly_position(dmy_id)
+                        .is_some_and(|position| {
+                            position.distance(camera_position) < proximity_hide.radius
+                        })
+                })
+            };
+
+            let is_left_near = is_near(&proximity_hide.left_dmy_polys);
+            let is_right_near = is_near(&proximity_hide.right_dmy_polys);
+
+            let is_left_shield = self.player.lh_weapon_param().is_some_and(|(_, row)| {
+                WeaponCategory::from_wep_type(row.wep_type()).is_some_and(WeaponCategory::is_shield)
+            });
+
+            let hide_left = match is_left_shield {
+                true => proximity_hide.shield,
+                false => proximity_hide.weapons,
+            };
+
+            if is_left_near && hide_left {
+                hidden.parts |= LEFT_WEAPON_SLOT;
+            }
+
+            if is_right_near && proximity_hide.weapons {
+                hidden.parts |= RIGHT_WEAPON_SLOT;
+            }
+
+            if is_left_near || is_right_near {
+                hidden = hidden.union(proximity_hide.arm_parts);
+            }
+        }
+
+        // Weapons are shown again by `enable_sheathed_weapons` every frame.
+        let mut shown = mem::replace(&mut self.proximity_hidden, hidden);
+        shown.parts &= !(hidden.parts | self.hidden_body_parts.parts);
+        shown.parts &= !(LEFT_WEAPON_SLOT | RIGHT_WEAPON_SLOT);
+
+        self.player.enable_body_parts(true, shown);
+        self.player.enable_body_parts(false, hidden);
+    }
+
+    fn update_dodge_fade(&mut self) {
+        let Some(dodge_transparency) = self.config.unobtrusive_dodges else {
+            let base_transparency = self.player.base_transparency;
+            self.player.base_transparency = self.dodge_fade.reset(base_transparency);
+            return;
+        };
+
+        let frame_time = self.frame_time.get(());
+        let is_dodging = self.has_state(BehaviorState::Evasion);
+
+        self.dodge_fade
+            .update(frame_time, is_dodging, &dodge_transparency);
+
+        let base_transparency = self.player.base_transparency;
+        self.player.base_transparency = self
+            .dodge_fade
+            .apply(base_transparency, dodge_transparency.opacity);
+    }
+
+    /// Whether the player is fighting, keeping the HUD and the crosshair visible.
+    fn in_combat(&self) -> bool {
+        // Targets in range of the lock on are not necessarily hostile, only an actual lock on is.
+        self.has_state(BehaviorState::Attack)
+            || self.has_state(BehaviorState::Damage)
+            || self.lock_tgt.locked_on_target().is_some()
+    }
+
+    fn update_hud(&mut self, first_person: bool) {
+        let Some(game_data_man) = (unsafe { GameDataMan::instance() }) else {
+            return;
+        };
+
+        let hud_type = &mut game_data_man.game_settings.hud_type;
+
+        let Some(minimal_hud) = self.config.minimal_hud.filter(|_| first_person) else {
+            self.hud.restore(hud_type);
+            self.hud.reset();
+            return;
+        };
+
+        let in_combat = self.in_combat();
+
+        // `GetAsyncKeyState` also reads keys pressed in other windows.
+        let show_key_held = Program::current().has_focus()
+            && (minimal_hud
+                .show_key
+                .is_some_and(|key| unsafe { GetAsyncKeyState(key.into()) } as u16 & 0x8000 != 0)
+                || minimal_hud.show_button.is_some_and(is_gamepad_button_held));
+
+        let data = &self.player.module_container.data;
+        let resources = [data.hp, data.fp, data.stamina];
+
+        let frame_time = self.frame_time.get(());
+        self.hud.update(
+            frame_time,
+            &minimal_hud,
+            HudInput {
+                in_combat,
+                show_key_held,
+                resources,
+            },
+        );
+
+        self.hud.apply(hud_type);
+    }
+
+    fn update_threat_markers(&self, camera_pos: F32ViewMatrix) {
+        let Some(radius) = self.config.threat_indicator_radius else {
+            set_threat_markers([0.0; MAX_MARKERS], [0.0; MAX_MARKERS]);
+            return;
+        };
+
+        let camera_position = Vec4::from(camera_pos.3).truncate();
+
+        let threats = world_characters().filter_map(|chr| {
+            // Skip distant characters before asking the game whether they are hostile.
+            if ptr::eq(chr, &self.player.chr_ins)
+                || chr.position().distance(camera_position) > radius
+                || !chr.is_alive()
+                || !self.player.can_target(chr)
+            {
+                return None;
+            }
+
+            let attacking = BehaviorStateSet::from_chr_ins(chr).has_state(BehaviorState::Attack);
+
+            Some(Threat {
+                position: chr.position(),
+                attacking,
+            })
+        });
+
+        let half_width = f32::tan(self.fov() * 0.5);
+        let half_extents = Vec2::new(half_width, half_width / self.aspect_ratio());
+
+        let (angles, alphas) = threat_markers(
+            threats,
+            camera_position,
+            camera_pos.rotation(),
+            half_extents,
+            radius,
+        );
+
+        set_threat_markers(angles, alphas);
+    }
+
+    fn update_crosshair(&mut self) {
+        let frame_time = self.frame_time.get(());
+
+        let on_target = (self.config.crosshair_feedback.highlight_targets
+            && self.crosshair.target_check_due(frame_time))
+        .then(|| {
+            self.target_under_crosshair(self.chr_cam.pers_cam.matrix)
+                .is_some()
+        });
+
+        let in_combat = self.in_combat();
+
+        let config = self.config;
+        self.crosshair.update(
+            config,
+            frame_time,
+            CrosshairInput {
+                on_target,
+                in_combat,
+            },
+        );
+    }
+
     pub fn is_player_sprinting(&self) -> bool {
         if self.config.restricted_sprint {
             self.player.is_sprinting()
@@ -457,12 +829,103 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
         }
     }
 
+    pub fn update_lock_on_target(&mut self) {
+        let target = self.lock_tgt.locked_on_target();
+
+        if let Some(lost_target) = self.lock_on.set_target(target)
+            && self.is_target_lost(lost_target)
+        {
+            let config = self.config;
+            self.lock_on.lose_target(config);
+        }
+
+        if target.is_none()
+            && self.config.reacquire_target
+            && self.lock_on.can_reacquire()
+            && let Some(next_target) = self.find_reacquire_target()
+        {
+            self.lock_on_to(next_target);
+        }
+    }
+
+    fn is_target_lost(&self, target: FieldInsHandle) -> bool {
+        // The player did not cancel the lock on, or the target is gone.
+        self.lock_tgt.is_lock_on_requested
+            || unsafe { ChrIns::from_handle(target) }.is_none_or(|chr| !chr.is_alive())
+    }
+
+    fn find_reacquire_target(&self) -> Option<FieldInsHandle> {
+        let camera_matrix = self.chr_cam.pers_cam.matrix;
+        let camera_position = Vec4::from(camera_matrix.3).truncate();
+        let camera_direction = Vec4::from(camera_matrix.2).truncate();
+
+        self.lock_tgt
+            .targets()
+            .filter_map(|handle| {
+                let chr = unsafe { ChrIns::from_handle(handle)? };
+
+                if !chr.is_alive() || !self.player.can_target(chr) {
+                    return None;
+                }
+
+                let lock_point = chr
+                    .dmy_poly_position(ChrIns::LOCK_ON_DMY_ID)
+                    .unwrap_or_else(|| chr.position());
+                let angle = camera_direction.angle_between(lock_point - camera_position);
+
+                (angle <= self.config.reacquire_angle).then_some((handle, angle))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(handle, _)| handle)
+    }
+
+    /// Hides the configured body parts in first person, showing the ones no longer hidden.
+    fn update_body_parts(&mut self) {
+        let mut hidden_body_parts = match self.first_person() {
+            true => self
+                .config
+                .hidden_body_parts(self.player.protector_param_ids()),
+            false => BodyParts::default(),
+        };
+
+        if self.first_person()
+            && let Some(body_awareness) = self.config.body_awareness
+        {
+            let forward = self.chr_cam.pers_cam.matrix.rotation::<Mat3A>().z_axis;
+            let pitch = forward.y.clamp(-1.0, 1.0).asin();
+
+            // Keep the torso hidden slightly above the threshold to avoid flickering.
+            const HYSTERESIS: f32 = f32::to_radians(5.0);
+
+            let threshold = match self.torso_hidden {
+                true => body_awareness.torso_pitch + HYSTERESIS,
+                false => body_awareness.torso_pitch,
+            };
+
+            self.torso_hidden = pitch < threshold;
+
+            if self.torso_hidden {
+                hidden_body_parts = hidden_body_parts.union(body_awareness.torso_parts);
+            }
+        } else {
+            self.torso_hidden = false;
+        }
+
+        if hidden_body_parts != self.hidden_body_parts {
+            let previous = mem::replace(&mut self.hidden_body_parts, hidden_body_parts);
+            self.player.enable_body_parts(true, previous);
+        }
+
+        self.player.enable_body_parts(false, hidden_body_parts);
+    }
+
     fn transition(&mut self) {
         self.first_person = !self.first_person;
 
         self.lock_tgt.is_lock_on_requested = false;
 
         self.update_fov_correction();
+        self.update_post_effects();
 
         let first_person = self.first_person();
 
@@ -471,49 +934,51 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
 
         self.set_crosshair_if(first_person);
 
-        self.player.enable_face_model(!first_person);
-        self.player.enable_sheathed_weapons(!first_person);
+        let config = self.config;
+
+        self.update_body_parts();
+        self.player
+            .enable_sheathed_weapons(!first_person, &config.sheathed_weapons);
 
         if !first_person {
-            self.player.make_transparent(false);
+            let proximity_hidden = mem::take(&mut self.proximity_hidden);
+            self.player.enable_body_parts(true, proximity_hidden);
+
+            let base_transparency = self.player.base_transparency;
+            self.player.base_transparency = self.dodge_fade.reset(base_transparency);
+
             self.lock_tgt.lock_camera = true;
 
             self.chr_cam.ex_follow_cam.lock_chase_rate = 0.3;
             self.chr_cam.ex_follow_cam.max_lock_target_offset = 0.05;
 
+            self.lock_on.reset();
+            self.crosshair.reset();
+
             self.player.chr_ctrl.scale_size_y = 1.0;
         } else {
             self.chr_cam.ex_follow_cam.max_lock_target_offset = 0.0;
         }
     }
 
-    pub fn update_behavior_states(&mut self) {
-        let mut behavior_set = BehaviorStateSet::default();
-
-        for node in self
+    pub fn update_weapon_override(&mut self) {
+        let weapon_override = self
             .player
-            .module_container
-            .behavior
-            .hkb_context
-            .hkb_character
-            .behavior_graph
-            .flat
-            .iter()
-            .map(|ptr| unsafe { ptr.as_ref() })
-        {
-            if node.flags[6] & 1 != 0 {
-                continue;
-            }
+            .held_weapon_params()
+            .into_iter()
+            .flatten()
+            .find_map(|(id, row)| {
+                let category = WeaponCategory::from_wep_type(row.wep_type());
+                self.config.weapon_overrides.get(id, category)
+            })
+            .copied()
+            .unwrap_or_default();
 
-            let name = unsafe { *node.unk08.byte_add(0x48).cast::<*const c_char>() };
-            if !name.is_null()
-                && let Ok(name) = unsafe { CStr::from_ptr(name).to_str() }
-                && let Ok(state) = name.try_into()
-            {
-                behavior_set.set_state(state);
-            }
-        }
+        self.weapon_override = weapon_override;
+    }
 
+    pub fn update_behavior_states(&mut self) {
+        let behavior_set = BehaviorStateSet::from_chr_ins(&self.player);
         self.behavior_states.push_state_set(behavior_set);
     }
 
@@ -524,6 +989,13 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
             return;
         }
 
+        if let Some(target) = self.target_under_crosshair(camera_pos) {
+            self.lock_on_to(target);
+        }
+    }
+
+    /// Casts a sphere from the camera, returning the targetable character in front of it.
+    fn target_under_crosshair(&self, camera_pos: F32ViewMatrix) -> Option<FieldInsHandle> {
         let origin = Vec4::from(camera_pos.3).truncate();
         let direction = Vec4::from(camera_pos.2).truncate() * 20.0;
 
@@ -539,12 +1011,9 @@ impl<'s> CoreLogicContext<'_, World<'s>> {
             })
         });
 
-        if let Some(hit) = hit
-            && let Some(field_ins_handle) = hit.field_ins_handle()
-            && field_ins_handle.selector.field_ins_type() == Some(FieldInsType::Chr)
-        {
-            self.lock_on_to(field_ins_handle);
-        }
+        let field_ins_handle = hit?.field_ins_handle()?;
+        (field_ins_handle.selector.field_ins_type() == Some(FieldInsType::Chr))
+            .then_some(field_ins_handle)
     }
 
     fn can_show_tutorial(&self) -> bool {
@@ -587,6 +1056,31 @@ impl<'s, W: WorldState> DerefMut for CoreLogicContext<'s, W> {
     }
 }
 
+/// Whether the button is held on the first controller.
+fn is_gamepad_button_held(button: GamepadButton) -> bool {
+    let flag = match button {
+        GamepadButton::None => return false,
+        GamepadButton::A => XINPUT_GAMEPAD_A,
+        GamepadButton::B => XINPUT_GAMEPAD_B,
+        GamepadButton::X => XINPUT_GAMEPAD_X,
+        GamepadButton::Y => XINPUT_GAMEPAD_Y,
+        GamepadButton::Back => XINPUT_GAMEPAD_BACK,
+        GamepadButton::Start => XINPUT_GAMEPAD_START,
+        GamepadButton::LeftShoulder => XINPUT_GAMEPAD_LEFT_SHOULDER,
+        GamepadButton::RightShoulder => XINPUT_GAMEPAD_RIGHT_SHOULDER,
+        GamepadButton::LeftThumb => XINPUT_GAMEPAD_LEFT_THUMB,
+        GamepadButton::RightThumb => XINPUT_GAMEPAD_RIGHT_THUMB,
+        GamepadButton::DpadUp => XINPUT_GAMEPAD_DPAD_UP,
+        GamepadButton::DpadDown => XINPUT_GAMEPAD_DPAD_DOWN,
+        GamepadButton::DpadLeft => XINPUT_GAMEPAD_DPAD_LEFT,
+        GamepadButton::DpadRight => XINPUT_GAMEPAD_DPAD_RIGHT,
+    };
+
+    let mut state = XINPUT_STATE::default();
+    unsafe { XInputGetState(0, &mut state) == ERROR_SUCCESS.0 }
+    &&state.Gamepad.wButtons.contains(flag)
+}
+
 unsafe impl Send for CoreLogic {}
 
 unsafe impl Sync for CoreLogic {}
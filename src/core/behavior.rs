@@ -1,4 +1,7 @@
+use std::ffi::{CStr, c_char};
+
 use bitvec::BitArr;
+use eldenring::cs::ChrIns;
 use strum::EnumCount;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumCount)]
@@ -20,9 +23,43 @@ pub struct BehaviorStates {
 }
 
 impl BehaviorStateSet {
+    /// Reads the active states from the behavior graph of a character.
+    pub fn from_chr_ins(chr: &ChrIns) -> Self {
+        let mut behavior_set = Self::default();
+
+        for node in chr
+            .module_container
+            .behavior
+            .hkb_context
+            .hkb_character
+            .behavior_graph
+            .flat
+            .iter()
+            .map(|ptr| unsafe { ptr.as_ref() })
+        {
+            if node.flags[6] & 1 != 0 {
+                continue;
+            }
+
+            let name = unsafe { *node.unk08.byte_add(0x48).cast::<*const c_char>() };
+            if !name.is_null()
+                && let Ok(name) = unsafe { CStr::from_ptr(name).to_str() }
+                && let Ok(state) = name.try_into()
+            {
+                behavior_set.set_state(state);
+            }
+        }
+
+        behavior_set
+    }
+
     pub fn set_state(&mut self, state: BehaviorState) {
         self.bits.set(state as usize, true);
     }
+
+    pub fn has_state(&self, state: BehaviorState) -> bool {
+        self.bits[state as usize]
+    }
 }
 
 impl BehaviorStates {
@@ -0,0 +1,45 @@
+use glam::Quat;
+
+#[derive(Default)]
+pub struct TunnelVision {
+    last_rotation: Option<Quat>,
+    intensity: f32,
+}
+
+impl TunnelVision {
+    /// Angular velocity of the camera (in radians per second) where the vignette starts.
+    const MIN_VELOCITY: f32 = 1.0;
+
+    /// Angular velocity of the camera (in radians per second) where the vignette is strongest.
+    const MAX_VELOCITY: f32 = 4.0;
+
+    /// How fast the vignette closes in.
+    const ATTACK_SPEED: f32 = 8.0;
+
+    /// How fast the vignette opens back up.
+    const RELEASE_SPEED: f32 = 2.0;
+
+    /// Updates the vignette from the camera rotation, returning its intensity.
+    pub fn update(&mut self, frame_time: f32, rotation: Quat) -> f32 {
+        let velocity = self.last_rotation.map_or(0.0, |last| {
+            last.angle_between(rotation) / frame_time.max(0.001)
+        });
+
+        self.last_rotation = Some(rotation);
+
+        let target = ((velocity - Self::MIN_VELOCITY) / (Self::MAX_VELOCITY - Self::MIN_VELOCITY))
+            .clamp(0.0, 1.0);
+
+        let speed = match target > self.intensity {
+            true => Self::ATTACK_SPEED,
+            false => Self::RELEASE_SPEED,
+        };
+
+        self.intensity += (target - self.intensity) * (1.0 - f32::exp(-frame_time * speed));
+        self.intensity
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,96 @@
+use crate::config::{Config, CrosshairBlend, CrosshairStyle};
+
+#[derive(Default)]
+pub struct CrosshairTracker {
+    highlight: f32,
+    on_target: bool,
+    fade: f32,
+    since_target_check: f32,
+    since_combat: f32,
+}
+
+pub struct CrosshairInput {
+    /// `None` between target checks, keeping the last result.
+    pub on_target: Option<bool>,
+    pub in_combat: bool,
+}
+
+pub struct CrosshairOutput {
+    pub scale: (f32, f32),
+    pub style: CrosshairStyle,
+}
+
+impl CrosshairTracker {
+    /// How fast the crosshair changes over a target.
+    const HIGHLIGHT_SPEED: f32 = 15.0;
+
+    /// How often the target under the crosshair is checked.
+    const TARGET_CHECK_INTERVAL: f32 = 0.1;
+
+    /// Duration of the out of combat fade.
+    const FADE_TIME: f32 = 0.5;
+
+    pub fn update(&mut self, config: &Config, frame_time: f32, input: CrosshairInput) {
+        let feedback = &config.crosshair_feedback;
+
+        if let Some(on_target) = input.on_target {
+            self.on_target = on_target;
+        }
+
+        let highlight = (feedback.highlight_targets && self.on_target) as u32 as f32;
+        let t = 1.0 - f32::exp(-frame_time * Self::HIGHLIGHT_SPEED);
+        self.highlight += (highlight - self.highlight) * t;
+
+        self.since_combat = match input.in_combat {
+            true => 0.0,
+            false => self.since_combat + frame_time,
+        };
+
+        let fade = feedback
+            .fade_delay
+            .is_some_and(|delay| self.since_combat > delay) as u32 as f32;
+
+        let fade_step = frame_time / Self::FADE_TIME;
+        self.fade = fade.clamp(self.fade - fade_step, self.fade + fade_step);
+    }
+
+    /// Returns whether the target under the crosshair should be checked this frame.
+    pub fn target_check_due(&mut self, frame_time: f32) -> bool {
+        self.since_target_check += frame_time;
+
+        let due = self.since_target_check >= Self::TARGET_CHECK_INTERVAL;
+        if due {
+            self.since_target_check = 0.0;
+        }
+
+        due
+    }
+
+    /// Applies the target highlight and the out of combat fade.
+    pub fn apply(&self, config: &Config, scale: (f32, f32)) -> CrosshairOutput {
+        let feedback = &config.crosshair_feedback;
+
+        let highlight_scale = 1.0 + (feedback.target_scale - 1.0) * self.highlight;
+
+        let mut style = config.crosshair_style;
+
+        for (color, target_color) in style.color.iter_mut().zip(feedback.target_color) {
+            *color += (target_color - *color) * self.highlight;
+        }
+
+        if style.blend == CrosshairBlend::Invert && self.highlight > 0.5 {
+            style.blend = CrosshairBlend::Solid;
+        }
+
+        style.opacity *= 1.0 - self.fade;
+
+        CrosshairOutput {
+            scale: (scale.0 * highlight_scale, scale.1 * highlight_scale),
+            style,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
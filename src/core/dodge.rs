@@ -0,0 +1,54 @@
+use crate::config::DodgeTransparency;
+
+/// Fades the player's `base_transparency` during dodges.
+#[derive(Default)]
+pub struct DodgeFade {
+    fade: f32,
+    written: Option<f32>,
+}
+
+impl DodgeFade {
+    pub fn update(&mut self, frame_time: f32, is_dodging: bool, config: &DodgeTransparency) {
+        let (target, time): (f32, f32) = match is_dodging {
+            true => (1.0, config.fade_in_time),
+            false => (0.0, config.fade_out_time),
+        };
+
+        let step = match time > 0.0 {
+            true => frame_time / time,
+            false => 1.0,
+        };
+
+        self.fade = target.clamp(self.fade - step, self.fade + step);
+    }
+
+    /// Returns the faded `base_transparency`, leaving it unchanged if another effect
+    /// (e.g. Unseen Form) changed it since the last write.
+    pub fn apply(&mut self, base_transparency: f32, opacity: f32) -> f32 {
+        let is_owned = match self.written {
+            Some(written) => base_transparency == written,
+            None => base_transparency == 1.0,
+        };
+
+        if !is_owned {
+            self.written = None;
+            return base_transparency;
+        }
+
+        let value = 1.0 + (opacity - 1.0) * self.fade;
+        self.written = (self.fade > 0.0).then_some(value);
+
+        value
+    }
+
+    /// Returns the restored `base_transparency` if it was last written by the fade.
+    pub fn reset(&mut self, base_transparency: f32) -> f32 {
+        let restored = match self.written {
+            Some(written) if base_transparency == written => 1.0,
+            _ => base_transparency,
+        };
+
+        *self = Self::default();
+        restored
+    }
+}
@@ -15,6 +15,7 @@ pub struct HeadTracker {
     rotation: Quat,
     rotation_target: Quat,
     stabilizer: CameraStabilizer,
+    head_height: Option<f32>,
     output: Option<Output>,
 }
 
@@ -23,6 +24,7 @@ pub struct Args {
     pub head_matrix: F32ModelMatrix,
     pub stabilizer_factor: f32,
     pub use_stabilizer: bool,
+    pub max_head_bob: Option<f32>,
     pub is_tracked: bool,
 }
 
@@ -33,6 +35,9 @@ pub struct Output {
 }
 
 impl HeadTracker {
+    /// How fast the resting head height follows the head.
+    const HEAD_HEIGHT_SPEED: f32 = 4.0;
+
     pub fn set_stabilizer_window(&mut self, window: f32) {
         self.stabilizer.set_window(window);
     }
@@ -52,9 +57,9 @@ impl FrameCache for HeadTracker {
     fn update(&mut self, frame_time: f32, args: Self::Input) -> Self::Output<'_> {
         let mut head_position = args.head_matrix.translation();
 
-        if args.use_stabilizer {
-            let player_matrix = Mat4::from(args.model_matrix);
+        let player_matrix = Mat4::from(args.model_matrix);
 
+        if args.use_stabilizer {
             let mut local_head_pos = player_matrix.inverse().project_point3(head_position);
 
             let stabilized = self.stabilizer.update(frame_time, local_head_pos);
@@ -65,6 +70,22 @@ impl FrameCache for HeadTracker {
             head_position = player_matrix.project_point3(local_head_pos);
         }
 
+        if let Some(max_head_bob) = args.max_head_bob {
+            let mut local_head_pos = player_matrix.inverse().project_point3(head_position);
+
+            let head_height = self.head_height.get_or_insert(local_head_pos.y);
+            let t = 1.0 - f32::exp(-frame_time * Self::HEAD_HEIGHT_SPEED);
+            *head_height += (local_head_pos.y - *head_height) * t;
+
+            local_head_pos.y = local_head_pos
+                .y
+                .clamp(*head_height - max_head_bob, *head_height + max_head_bob);
+
+            head_position = player_matrix.project_point3(local_head_pos);
+        } else {
+            self.head_height = None;
+        }
+
         let input = Quat::from_mat3a(&args.head_matrix.rotation());
 
         if args.is_tracked
@@ -92,6 +113,7 @@ impl FrameCache for HeadTracker {
 
     fn reset(&mut self) {
         self.stabilizer.reset();
+        self.head_height = None;
         self.last = None;
     }
 }
@@ -110,6 +132,7 @@ impl From<&CoreLogicContext<'_, World<'_>>> for Args {
             model_matrix,
             stabilizer_factor: context.config.stabilizer_factor,
             use_stabilizer: context.config.use_stabilizer,
+            max_head_bob: context.config.comfort.max_head_bob,
             is_tracked,
         }
     }
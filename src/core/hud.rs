@@ -0,0 +1,89 @@
+use eldenring::cs::HudType;
+
+use crate::config::MinimalHud;
+
+#[derive(Default)]
+pub struct HudTracker {
+    since_activity: f32,
+    last_resources: Option<[i32; 3]>,
+    fade: f32,
+    saved_hud_type: Option<HudType>,
+}
+
+pub struct HudInput {
+    pub in_combat: bool,
+    pub show_key_held: bool,
+    /// HP, FP and stamina of the player.
+    pub resources: [i32; 3],
+}
+
+impl HudTracker {
+    /// Updates the time since the last activity and fades the HUD in or out.
+    pub fn update(&mut self, frame_time: f32, minimal_hud: &MinimalHud, input: HudInput) {
+        // Taking damage or spending FP or stamina.
+        let spent_resources = self.last_resources.is_some_and(|last_resources| {
+            input
+                .resources
+                .iter()
+                .zip(last_resources)
+                .any(|(&resource, last_resource)| resource < last_resource)
+        });
+
+        self.last_resources = Some(input.resources);
+
+        self.since_activity = match input.in_combat || input.show_key_held || spent_resources {
+            true => 0.0,
+            false => self.since_activity + frame_time,
+        };
+
+        let hide = self.since_activity > minimal_hud.hide_delay;
+
+        let fade_time = match hide {
+            true => minimal_hud.fade_out_time,
+            false => minimal_hud.fade_in_time,
+        };
+
+        let fade_step = frame_time / fade_time.max(f32::EPSILON);
+        self.fade = (hide as u32 as f32).clamp(self.fade - fade_step, self.fade + fade_step);
+    }
+
+    /// Opacity of the crosshair hidden with the HUD.
+    pub fn opacity(&self) -> f32 {
+        1.0 - self.fade
+    }
+
+    /// Turns the game's HUD off once faded out and back on as it fades in.
+    pub fn apply(&mut self, hud_type: &mut HudType) {
+        match self.fade >= 1.0 {
+            true => self.hide(hud_type),
+            false => self.restore(hud_type),
+        }
+    }
+
+    /// Turns the game's HUD off, saving the setting to restore.
+    fn hide(&mut self, hud_type: &mut HudType) {
+        if self.saved_hud_type.is_none() && *hud_type != HudType::Off {
+            self.saved_hud_type = Some(*hud_type);
+            *hud_type = HudType::Off;
+        }
+    }
+
+    /// Restores the game's HUD setting, unless it was changed in the options meanwhile.
+    pub fn restore(&mut self, hud_type: &mut HudType) {
+        if let Some(saved_hud_type) = self.saved_hud_type.take()
+            && *hud_type == HudType::Off
+        {
+            *hud_type = saved_hud_type;
+        }
+    }
+
+    /// Whether the game's HUD is off because of the minimal HUD.
+    pub fn is_hiding(&self) -> bool {
+        self.saved_hud_type.is_some()
+    }
+
+    /// Starts over, once the game's HUD setting is restored.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
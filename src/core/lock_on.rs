@@ -0,0 +1,189 @@
+use std::f32::consts::PI;
+
+use eldenring::cs::{ChrIns, FieldInsHandle};
+use glam::{Quat, Vec2, Vec3};
+
+use crate::{
+    chr::ChrInsExt,
+    config::{Config, LockOnAimPoint},
+};
+
+#[derive(Default)]
+pub struct LockOnTracker {
+    ramp: f32,
+    offset: Vec2,
+    target: Option<FieldInsHandle>,
+    reacquire_time: f32,
+    free_look_ease: Option<FreeLookEase>,
+    last_rotation: Quat,
+}
+
+struct FreeLookEase {
+    from: Quat,
+    elapsed: f32,
+    duration: f32,
+}
+
+pub struct Target {
+    pub lock_point: Vec3,
+    pub aim_point: Vec3,
+}
+
+impl LockOnTracker {
+    /// Chest height as a fraction of head height.
+    const CHEST_HEIGHT: f32 = 0.8;
+
+    /// How fast the aim point offset follows the target, scaled by the max chase rate.
+    const OFFSET_CHASE_SPEED: f32 = 8.0;
+
+    /// Advances the chase rate ramp and the aim point offset, returning the new lock chase rate.
+    pub fn update(
+        &mut self,
+        config: &Config,
+        frame_time: f32,
+        camera_position: Vec3,
+        camera_direction: Vec3,
+        target: Option<&Target>,
+    ) -> f32 {
+        self.reacquire_time = (self.reacquire_time - frame_time).max(0.0);
+
+        if let Some(ease) = &mut self.free_look_ease {
+            ease.elapsed += frame_time;
+
+            if ease.elapsed >= ease.duration {
+                self.free_look_ease = None;
+            }
+        }
+
+        let ramp_step = frame_time / config.lock_chase_ramp_time;
+
+        self.ramp = match target {
+            Some(_) => (self.ramp + ramp_step).min(1.0),
+            None => (self.ramp - ramp_step).max(0.0),
+        };
+
+        let (min, max) = config.lock_chase_rate;
+        let mut chase_rate = min + (max - min) * config.lock_chase_curve.apply(self.ramp);
+
+        let offset = if let Some(target) = target {
+            let aim_direction = target.aim_point - camera_position;
+            let lock_direction = target.lock_point - camera_position;
+
+            let dead_zone = config.lock_on_dead_zone;
+            if dead_zone > 0.0 {
+                let error = camera_direction.angle_between(aim_direction);
+                chase_rate *= smoothstep(dead_zone, dead_zone * 2.0, error);
+            }
+
+            let offset = yaw_pitch(aim_direction) - yaw_pitch(lock_direction);
+            Vec2::new(wrap_angle(offset.x), offset.y)
+        } else {
+            Vec2::ZERO
+        };
+
+        let t = 1.0 - f32::exp(-max * frame_time * Self::OFFSET_CHASE_SPEED);
+        self.offset = self.offset.lerp(offset, t);
+
+        chase_rate
+    }
+
+    /// Rotates the camera from the game's lock on point to the configured aim point,
+    /// easing back to free look after losing a target.
+    pub fn apply(&self, camera_rotation: Quat) -> Quat {
+        let mut rotation = Quat::from_rotation_y(self.offset.x)
+            * camera_rotation
+            * Quat::from_rotation_x(-self.offset.y);
+
+        if let Some(ease) = &self.free_look_ease {
+            let t = smoothstep(0.0, 1.0, ease.elapsed / ease.duration);
+            rotation = ease.from.slerp(rotation, t);
+        }
+
+        rotation
+    }
+
+    /// Remembers the rotation [`LockOnTracker::apply`] gives this frame, to ease from
+    /// once the target is lost.
+    pub fn remember_rotation(&mut self, camera_rotation: Quat) {
+        self.last_rotation = self.apply(camera_rotation);
+    }
+
+    /// Remembers the current lock on target, returning the previous one once it is gone.
+    pub fn set_target(&mut self, target: Option<FieldInsHandle>) -> Option<FieldInsHandle> {
+        if target.is_some() {
+            self.reacquire_time = 0.0;
+            self.target = target;
+            None
+        } else {
+            self.target.take()
+        }
+    }
+
+    /// Starts the reacquisition grace period and eases the camera back to free look.
+    pub fn lose_target(&mut self, config: &Config) {
+        self.reacquire_time = config.reacquire_grace_period;
+
+        if config.free_look_ease_time > 0.0 {
+            self.free_look_ease = Some(FreeLookEase {
+                from: self.last_rotation,
+                elapsed: 0.0,
+                duration: config.free_look_ease_time,
+            });
+        }
+    }
+
+    pub fn can_reacquire(&self) -> bool {
+        self.reacquire_time > 0.0
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Target {
+    pub fn new(chr: &ChrIns, aim_point: LockOnAimPoint, aim_height: f32) -> Self {
+        let lock_point = chr
+            .dmy_poly_position(ChrIns::LOCK_ON_DMY_ID)
+            .unwrap_or_else(|| chr.position());
+
+        // Not every model has a head dummy poly, aim at the lock on point without one.
+        let head = chr.dmy_poly_position(ChrIns::HEAD_DMY_ID);
+
+        let height_point = |fraction: f32| {
+            head.map_or(lock_point, |head| {
+                let feet = chr.position();
+                feet.with_y(feet.y + (head.y - feet.y) * fraction)
+            })
+        };
+
+        let aim_point = match aim_point {
+            LockOnAimPoint::LockPoint => lock_point,
+            LockOnAimPoint::Head => head.unwrap_or(lock_point),
+            LockOnAimPoint::Chest => height_point(LockOnTracker::CHEST_HEIGHT),
+            LockOnAimPoint::Height => height_point(aim_height),
+        };
+
+        Self {
+            lock_point,
+            aim_point,
+        }
+    }
+}
+
+fn yaw_pitch(direction: Vec3) -> Vec2 {
+    let direction = direction.normalize_or_zero();
+    Vec2::new(
+        direction.x.atan2(direction.z),
+        direction.y.clamp(-1.0, 1.0).asin(),
+    )
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
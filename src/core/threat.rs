@@ -0,0 +1,64 @@
+use glam::{Mat3A, Vec2, Vec3};
+
+use crate::shaders::payload::MAX_MARKERS;
+
+pub struct Threat {
+    pub position: Vec3,
+    pub attacking: bool,
+}
+
+/// Maximum number of markers shown at once, packed into `g_ErfpsMarkers.x`.
+pub const MAX_THREATS: usize = MAX_MARKERS;
+
+/// Opacity of the marker of a threat that is not attacking.
+const IDLE_ALPHA: f32 = 0.5;
+
+/// Angles of the markers around the center of the screen (0.0 is ahead, positive values
+/// are to the right) and their opacity, for the closest threats outside the view.
+pub fn threat_markers(
+    threats: impl IntoIterator<Item = Threat>,
+    camera_position: Vec3,
+    camera_rotation: Mat3A,
+    half_extents: Vec2,
+    radius: f32,
+) -> ([f32; MAX_THREATS], [f32; MAX_THREATS]) {
+    let mut markers = threats
+        .into_iter()
+        .filter_map(|threat| {
+            let local = camera_rotation.transpose() * (threat.position - camera_position);
+            let distance = local.length();
+
+            if distance > radius || is_in_view(local, half_extents) {
+                return None;
+            }
+
+            let fade = 1.0 - distance / radius;
+            let alpha = match threat.attacking {
+                true => fade,
+                false => fade * IDLE_ALPHA,
+            };
+
+            Some((distance, local.x.atan2(local.z), alpha))
+        })
+        .collect::<Vec<_>>();
+
+    markers.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+
+    let mut angles = [0.0; MAX_THREATS];
+    let mut alphas = [0.0; MAX_THREATS];
+
+    for (i, (_, angle, alpha)) in markers.into_iter().take(MAX_THREATS).enumerate() {
+        angles[i] = angle;
+        alphas[i] = alpha;
+    }
+
+    (angles, alphas)
+}
+
+/// Whether a point in camera space is inside the view frustum, given the tangents of the
+/// half FOV.
+fn is_in_view(local: Vec3, half_extents: Vec2) -> bool {
+    local.z > 0.0
+        && local.x.abs() <= local.z * half_extents.x
+        && local.y.abs() <= local.z * half_extents.y
+}
@@ -1,4 +1,6 @@
-use eldenring::cs::{GameDataMan, HudType};
+use std::iter;
+
+use eldenring::cs::{FieldInsHandle, GameDataMan, HudType, LockTgtMan};
 
 use crate::{program::Program, rva::GAME_DATA_MAN_RVA};
 
@@ -8,6 +10,12 @@ pub trait GameDataManExt {
     fn is_hud_enabled(&self) -> bool;
 }
 
+pub trait LockTgtManExt {
+    fn locked_on_target(&self) -> Option<FieldInsHandle>;
+
+    fn targets(&self) -> impl Iterator<Item = FieldInsHandle>;
+}
+
 impl GameDataManExt for GameDataMan {
     unsafe fn instance() -> Option<&'static mut GameDataMan> {
         unsafe {
@@ -22,3 +30,35 @@ impl GameDataManExt for GameDataMan {
         self.game_settings.hud_type != HudType::Off
     }
 }
+
+impl LockTgtManExt for LockTgtMan {
+    fn locked_on_target(&self) -> Option<FieldInsHandle> {
+        if !self.is_locked_on {
+            return None;
+        }
+
+        let mut next_node = self.nodes;
+
+        while let Some(node) = next_node.map(|ptr| unsafe { ptr.as_ref() }) {
+            next_node = node.next;
+
+            // See `CoreLogicContext::lock_on_to`.
+            if node.flags & 32 != 0 {
+                return Some(unsafe { node.value.as_ref().chr_handle });
+            }
+        }
+
+        None
+    }
+
+    fn targets(&self) -> impl Iterator<Item = FieldInsHandle> {
+        let mut next_node = self.nodes;
+
+        iter::from_fn(move || {
+            let node = unsafe { next_node?.as_ref() };
+            next_node = node.next;
+
+            Some(unsafe { node.value.as_ref().chr_handle })
+        })
+    }
+}
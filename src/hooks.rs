@@ -22,8 +22,12 @@ use crate::{
         CAMERA_STEP_UPDATE_RVA, CHR_ROOT_MOTION_RVA, FOLLOW_CAM_FOLLOW_RVA, MMS_UPDATE_CHR_CAM_RVA,
         POSTURE_CONTROL_RIGHT_RVA, PUSH_TAE700_MODIFIER_RVA, SET_WWISE_LISTENER_RVA,
         UPDATE_CHR_MODEL_POS_RVA, UPDATE_FE_MAN_RVA, UPDATE_FOLLOW_CAM_RVA, UPDATE_LOCK_TGT_RVA,
+        posture_control_left_rva,
+    },
+    shaders::{
+        screen::{correct_screen_coords, hud_layout_size},
+        submit_shader_payload,
     },
-    shaders::screen::correct_screen_coords,
 };
 
 pub mod install;
@@ -137,11 +141,31 @@ pub fn init_camera_update(program: Program) -> eyre::Result<()> {
 
         hook(posture_control_right, |original| {
             move |param_1, param_2, param_3, param_4| {
-                let posture_angle = hand_posture_control(**param_1).unwrap_or(0);
+                let posture_angle = hand_posture_control(**param_1, 1).unwrap_or(0);
                 original(param_1, param_2, param_3, param_4) + posture_angle
             }
         });
 
+        if let Some(posture_control_left_rva) = posture_control_left_rva() {
+            let posture_control_left = program.derva_ptr::<unsafe extern "C" fn(
+                *mut *mut *mut PlayerIns,
+                u8,
+                i32,
+                i32,
+            ) -> i32>(posture_control_left_rva);
+
+            hook(posture_control_left, |original| {
+                move |param_1, param_2, param_3, param_4| {
+                    let posture_angle = hand_posture_control(**param_1, 0).unwrap_or(0);
+                    original(param_1, param_2, param_3, param_4) + posture_angle
+                }
+            });
+        } else {
+            log::warn!(
+                "left hand posture routine not found, left hand posture offsets are ignored"
+            );
+        }
+
         let chr_root_motion = program.derva_ptr::<unsafe extern "C" fn(
             *mut CSChrPhysicsModule,
             *mut F32Vector4,
@@ -197,11 +221,14 @@ unsafe fn update_move_map_step(original: &dyn Fn()) {
     CoreLogic::scope_mut::<Void, _>(|context| context.next_frame());
 
     CoreLogic::scope_mut::<World, _>(|context| {
+        context.update_weapon_override();
         context.update_behavior_states();
         context.try_transition();
         context.update_chr_cam();
     });
 
+    submit_shader_payload();
+
     original();
 }
 
@@ -210,6 +237,10 @@ unsafe fn update_lock_tgt(original: &dyn Fn()) {
     original();
 
     CoreLogic::scope_mut::<World, _>(|context| {
+        if context.first_person() {
+            context.update_lock_on_target();
+        }
+
         if context.first_person()
             && !context.can_transition()
             && !context.is_player_sprinting()
@@ -242,26 +273,34 @@ unsafe fn update_fe_man() {
         return;
     };
 
-    const FE_XY: Vec2 = Vec2::new(1920.0, 1080.0);
+    let hud_correction = CoreLogic::scope::<Void, _>(|context| context.config.hud_correction);
+
+    let fe_xy = hud_layout_size();
     let correct_coords = |coords: &mut F32Vector4| {
         let screen_coords = Vec2::new(coords.0, coords.1);
-        let corrected_screen_coords = correct_screen_coords(screen_coords / FE_XY) * FE_XY;
+        let corrected_screen_coords = correct_screen_coords(screen_coords / fe_xy) * fe_xy;
 
         coords.0 = corrected_screen_coords.x;
         coords.1 = corrected_screen_coords.y;
     };
 
-    correct_coords(&mut fe_man.lock_on_pos);
+    if hud_correction.lock_on {
+        correct_coords(&mut fe_man.lock_on_pos);
+    }
 
-    for tag in &mut fe_man.enemy_chr_tag_displays {
-        if tag.is_visible {
-            correct_coords(&mut tag.screen_pos);
+    if hud_correction.enemy_tags {
+        for tag in &mut fe_man.enemy_chr_tag_displays {
+            if tag.is_visible {
+                correct_coords(&mut tag.screen_pos);
+            }
         }
     }
 
-    for tag in &mut fe_man.friendly_chr_tag_displays {
-        if tag.is_visible {
-            correct_coords(&mut tag.screen_pos);
+    if hud_correction.friendly_tags {
+        for tag in &mut fe_man.friendly_chr_tag_displays {
+            if tag.is_visible {
+                correct_coords(&mut tag.screen_pos);
+            }
         }
     }
 }
@@ -291,16 +330,20 @@ unsafe fn tae700_override(args: &mut [f32; 8]) {
     args[5] = 0.0;
 }
 
+/// The posture angle added to the left (0) or right (1) hand.
 #[cfg_attr(debug_assertions, libhotpatch::hotpatch)]
-unsafe fn hand_posture_control(some_player: *const PlayerIns) -> Option<i32> {
+unsafe fn hand_posture_control(some_player: *const PlayerIns, hand: usize) -> Option<i32> {
     let main_player = unsafe { PlayerIns::main_player()? };
     let is_main_player = ptr::eq(some_player, main_player);
 
-    if !is_main_player || main_player.is_2h() || !CoreLogic::is_first_person() {
+    if !is_main_player || !CoreLogic::is_first_person() {
         return None;
     }
 
-    Some(15)
+    let layout = main_player.hand_layout();
+    Some(CoreLogic::scope::<Void, _>(|context| {
+        context.posture_offsets(layout)[hand]
+    }))
 }
 
 #[cfg_attr(debug_assertions, libhotpatch::hotpatch)]
@@ -313,7 +356,7 @@ unsafe fn root_motion_modifier(
 
     if !is_main_player
         || !CoreLogic::scope::<Void, _>(|context| {
-            context.config.unlocked_movement
+            context.unlocked_movement()
                 && context.first_person()
                 && context.has_state(BehaviorState::Attack)
         })
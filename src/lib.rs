@@ -7,6 +7,7 @@ use windows::{
 
 use crate::{hooks::init_camera_update, program::Program, shaders::hook_shaders};
 
+mod chr;
 mod config;
 mod core;
 mod game;
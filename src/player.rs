@@ -1,3 +1,5 @@
+use std::array;
+
 use eldenring::{
     cs::{
         CSModelIns, ChrAsmArmStyle, ChrIns, ChrMovementLimit, PlayerIns, ThrowNodeState,
@@ -10,10 +12,19 @@ use fromsoftware_shared::{F32ModelMatrix, F32Vector4, F32ViewMatrix, FromStatic,
 use glam::{Vec3, Vec4, Vec4Swizzles};
 
 use crate::{
+    chr::ChrInsExt,
+    config::{BodyParts, HandLayout, SheathedWeapons, WeaponCategory},
     program::Program,
-    rva::{CHR_CAN_TARGET_RVA, GET_DMY_POS_RVA},
+    rva::CHR_CAN_TARGET_RVA,
 };
 
+/// Index of the head armor in `ChrAsm::equipment_param_ids`, followed by chest, arms and legs.
+///
+/// The slots follow the equipment order of the save data: three pairs of left and right weapons
+/// (see `lh_weapon_param` and `rh_weapon_param`), two pairs of arrows and bolts, two unused
+/// slots, then the armor.
+const PROTECTOR_HEAD_SLOT: usize = 12;
+
 pub trait PlayerExt {
     const HEIGHT: f32 = 1.7;
 
@@ -33,11 +44,9 @@ pub trait PlayerExt {
 
     fn set_lock_on_target_position(&mut self, pos: Vec4);
 
-    fn make_transparent(&mut self, state: bool);
+    fn enable_body_parts(&mut self, state: bool, body_parts: BodyParts);
 
-    fn enable_face_model(&mut self, state: bool);
-
-    fn enable_sheathed_weapons(&mut self, state: bool);
+    fn enable_sheathed_weapons(&mut self, state: bool, rules: &SheathedWeapons);
 
     fn cancel_sprint(&mut self);
 
@@ -57,9 +66,16 @@ pub trait PlayerExt {
 
     fn is_2h(&self) -> bool;
 
+    fn hand_layout(&self) -> HandLayout;
+
+    /// Param ids of the head, chest, arms and legs armor.
+    fn protector_param_ids(&self) -> [u32; 4];
+
     fn lh_weapon_param(&self) -> Option<(u32, &'static EQUIP_PARAM_WEAPON_ST)>;
 
     fn rh_weapon_param(&self) -> Option<(u32, &'static EQUIP_PARAM_WEAPON_ST)>;
+
+    fn held_weapon_params(&self) -> [Option<(u32, &'static EQUIP_PARAM_WEAPON_ST)>; 2];
 }
 
 impl PlayerExt for PlayerIns {
@@ -75,23 +91,9 @@ impl PlayerExt for PlayerIns {
     }
 
     fn head_matrix(&self) -> F32ModelMatrix {
-        type GetDmyPos = unsafe extern "C" fn(
-            *const ChrIns,
-            *mut F32ModelMatrix,
-            *const u32,
-            i32,
-        ) -> *mut F32Vector4;
-
-        // Fetch a model matrix for the head dummy poly in world space.
-        const HEAD_DMY_ID: u32 = 907;
-        unsafe {
-            let get_dmy_pos = Program::current().derva_ptr::<GetDmyPos>(GET_DMY_POS_RVA);
-
-            let mut dmy_pos = F32ModelMatrix::IDENTITY;
-            get_dmy_pos(&**self, &mut dmy_pos, &HEAD_DMY_ID, 1);
-
-            dmy_pos
-        }
+        self.chr_ins
+            .dmy_poly_matrix(ChrIns::HEAD_DMY_ID)
+            .unwrap_or(self.chr_ctrl.model_matrix)
     }
 
     fn location_entity_matrix_mut(&mut self) -> &mut F32ModelMatrix {
@@ -121,44 +123,42 @@ impl PlayerExt for PlayerIns {
         self.lock_on_target_position = pos.with_w(1.0).into();
     }
 
-    fn make_transparent(&mut self, state: bool) {
-        // Magic constant (about 0.4683).
-        // Prevents overwriting values other than our bit pattern.
-        const MAGIC: f32 = -f32::from_bits(0xbeefbeef);
-
-        if state && self.base_transparency == 1.0 {
-            self.base_transparency = MAGIC;
-        }
-
-        if !state && self.base_transparency == MAGIC {
-            self.base_transparency = 1.0;
-        }
-    }
-
-    fn enable_face_model(&mut self, state: bool) {
+    fn enable_body_parts(&mut self, state: bool, body_parts: BodyParts) {
         // Toggle face, helmet, hair, eyes, etc. visibility but still cast a shadow.
-        for parts in self.chr_asm_model_ins.iter_mut().flat_map(|ptr| unsafe {
+        for parts in self.chr_asm_model_ins.iter_mut().flat_map(|ptr| {
             ptr.parts_model_ins
-                .get_disjoint_unchecked_mut([0, 2, 6, 21, 22, 23, 24, 25])
+                .iter_mut()
+                .enumerate()
+                .filter(|(slot, _)| body_parts.has_part(*slot))
+                .map(|(_, parts)| parts)
         }) {
             enable_parts_visibilty(parts, state);
         }
 
-        // Toggle back of head and ears visibility (which otherwise may clip into view).
+        // Toggle back of head, ears, hoods etc. visibility (which otherwise may clip into view).
+        // Each set bit of a model mask hides the corresponding geometry.
         if let Some(chr_asm_model_res) = self.chr_asm_model_res {
-            // Model mask 3 (bit 3 in the u64 at +0x58).
-            let ears_model_mask = unsafe { chr_asm_model_res.add(0x58).as_mut() };
-            *ears_model_mask = *ears_model_mask & !8 | (!state as u8) << 3;
-            // Model mask 36 (bit 11 in the u64 at +0x48).
-            let hood_model_mask = unsafe { chr_asm_model_res.add(0x49).as_mut() };
-            *hood_model_mask = *hood_model_mask & !8 | (!state as u8) << 3;
-            // Model mask ??
-            let hood_model_mask2 = unsafe { chr_asm_model_res.add(0x50).as_mut() };
-            *hood_model_mask2 = *hood_model_mask2 & !32 | (!state as u8) << 5;
+            for (i, mask) in body_parts.model_masks.into_iter().enumerate() {
+                if mask == 0 {
+                    continue;
+                }
+
+                let model_mask = unsafe {
+                    chr_asm_model_res
+                        .add(BodyParts::MODEL_MASK_OFFSET + i * 8)
+                        .cast::<u64>()
+                        .as_mut()
+                };
+
+                match state {
+                    true => *model_mask &= !mask,
+                    false => *model_mask |= mask,
+                }
+            }
         }
     }
 
-    fn enable_sheathed_weapons(&mut self, state: bool) {
+    fn enable_sheathed_weapons(&mut self, state: bool, rules: &SheathedWeapons) {
         let is_riding = self.is_riding();
 
         let lh_weapon_param = self.lh_weapon_param();
@@ -184,20 +184,20 @@ impl PlayerExt for PlayerIns {
 
         let [lh_weapon, lh_sheath, rh_weapon, rh_sheath] = parts;
 
-        let (lh_weapon_visibility, rh_weapon_visibility) = match self.chr_asm.equipment.arm_style {
-            ChrAsmArmStyle::OneHanded if is_riding => (false, true),
-            ChrAsmArmStyle::LeftBothHands => (true, false),
-            ChrAsmArmStyle::RightBothHands => (false, true),
-            _ => (true, true),
+        let [lh_weapon_visibility, rh_weapon_visibility] = match self.chr_asm.equipment.arm_style {
+            _ if is_riding => rules.mounted,
+            ChrAsmArmStyle::LeftBothHands => rules.left_both_hands,
+            ChrAsmArmStyle::RightBothHands => rules.right_both_hands,
+            _ => rules.one_handed,
         };
 
-        // Coded Sword uses the sheath model slot for its blade.
-        const CODED_SWORD_ID: u32 = 2110000;
+        let has_sheath = |(id, row): (u32, &EQUIP_PARAM_WEAPON_ST)| {
+            let category = WeaponCategory::from_wep_type(row.wep_type());
+            rules.has_sheath(id, category, row.is_dual_blade() != 0)
+        };
 
-        let lh_sheath_visibility = lh_weapon_param
-            .is_some_and(|(id, row)| id == CODED_SWORD_ID || row.is_dual_blade() != 0);
-        let rh_sheath_visibility = rh_weapon_param
-            .is_some_and(|(id, row)| id == CODED_SWORD_ID || row.is_dual_blade() != 0);
+        let lh_sheath_visibility = lh_weapon_param.is_some_and(has_sheath);
+        let rh_sheath_visibility = rh_weapon_param.is_some_and(has_sheath);
 
         enable_parts_visibilty(lh_weapon, lh_weapon_visibility);
         enable_parts_visibilty(rh_weapon, rh_weapon_visibility);
@@ -267,6 +267,32 @@ impl PlayerExt for PlayerIns {
                     .is_none_or(|(_, row)| row.is_dual_blade() == 0)
     }
 
+    fn hand_layout(&self) -> HandLayout {
+        if self.is_2h() {
+            return HandLayout::TwoHanded;
+        }
+
+        // Dual blades held in both hands are not `is_2h`.
+        let is_dual_blade = matches!(
+            self.chr_asm.equipment.arm_style,
+            ChrAsmArmStyle::LeftBothHands | ChrAsmArmStyle::RightBothHands
+        );
+
+        let is_paired = self
+            .lh_weapon_param()
+            .zip(self.rh_weapon_param())
+            .is_some_and(|((_, lh_row), (_, rh_row))| lh_row.wep_type() == rh_row.wep_type());
+
+        match is_dual_blade || is_paired {
+            true => HandLayout::DualWield,
+            false => HandLayout::OneHanded,
+        }
+    }
+
+    fn protector_param_ids(&self) -> [u32; 4] {
+        array::from_fn(|i| self.chr_asm.equipment_param_ids[PROTECTOR_HEAD_SLOT + i] as u32)
+    }
+
     fn lh_weapon_param(&self) -> Option<(u32, &'static EQUIP_PARAM_WEAPON_ST)> {
         let chr_asm = self.chr_asm.as_ref();
 
@@ -286,6 +312,14 @@ impl PlayerExt for PlayerIns {
         let weapon_param = unsafe { FD4ParamRepository::instance().ok()?.get(weapon_param_id)? };
         Some((weapon_param_id, weapon_param))
     }
+
+    fn held_weapon_params(&self) -> [Option<(u32, &'static EQUIP_PARAM_WEAPON_ST)>; 2] {
+        // The weapon held in both hands comes first, then the right hand weapon.
+        match self.chr_asm.equipment.arm_style {
+            ChrAsmArmStyle::LeftBothHands => [self.lh_weapon_param(), self.rh_weapon_param()],
+            _ => [self.rh_weapon_param(), self.lh_weapon_param()],
+        }
+    }
 }
 
 fn enable_parts_visibilty(parts: &mut Option<OwnedPtr<CSModelIns>>, state: bool) {
@@ -1,8 +1,12 @@
-use std::{ffi::c_void, mem, sync::LazyLock};
+use std::{ffi::c_void, mem, process, sync::LazyLock};
 
 use pelite::pe::PeView;
 use windows::{
-    Win32::{Foundation::HMODULE, System::LibraryLoader::GetModuleHandleW},
+    Win32::{
+        Foundation::HMODULE,
+        System::LibraryLoader::GetModuleHandleW,
+        UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
+    },
     core::Error,
     core::PCWSTR,
 };
@@ -39,6 +43,17 @@ impl Program {
         unsafe { mem::transmute_copy::<*mut (), P>(&self.derva::<()>(rva)) }
     }
 
+    /// Whether the foreground window belongs to the game.
+    pub fn has_focus(self) -> bool {
+        let mut process_id = 0;
+
+        unsafe {
+            GetWindowThreadProcessId(GetForegroundWindow(), Some(&raw mut process_id));
+        }
+
+        process_id == process::id()
+    }
+
     fn from_hmodule(HMODULE(base): HMODULE) -> Self {
         Self(base)
     }
@@ -1,6 +1,7 @@
 use std::{ops::Deref, sync::LazyLock};
 
 use eldenring::version::GameVersion;
+use pelite::pe::{Pe, PeView};
 
 use crate::program::Program;
 
@@ -69,3 +70,21 @@ rva! {
     UPDATE_LOCK_TGT_RVA,
     USES_DITHERING_RVA,
 }
+
+/// The left hand posture routine, called right after `POSTURE_CONTROL_RIGHT_RVA`.
+///
+/// Not mapped by binary-mapper, so it is found in the running game instead.
+pub fn posture_control_left_rva() -> Option<Rva> {
+    let pattern = pelite::pattern!(
+        "40 88 7c 24 20 45 8b cd 45 8b c4 41 0f b6 d7 48 8d 4d d7 e8 $ { ' } 8b d8 45 8b cd 45 8b c4 41 0f b6 d7 48 8d 4c 24 30 e8 $ { ' }"
+    );
+
+    let mut save = [0; 3];
+    let found = PeView::from(Program::current())
+        .scanner()
+        .finds_code(pattern, &mut save);
+
+    // Only trusted if the first call is the mapped right hand routine.
+    let [_, right, left] = save;
+    (found && right == *POSTURE_CONTROL_RIGHT_RVA && left != right).then(|| Rva::new(left, left))
+}
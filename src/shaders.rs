@@ -1,24 +1,39 @@
 use std::{
     arch::naked_asm,
+    cell::UnsafeCell,
     ffi::c_void,
-    sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    mem::offset_of,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicPtr, Ordering},
+    },
 };
 
+use glam::Vec2;
 use windows::{
     Win32::System::Memory::{PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS, VirtualProtect},
     core::PCWSTR,
 };
 
 use crate::{
-    config::CrosshairKind,
+    config::{CrosshairKind, CrosshairShape, CrosshairStyle, FovProjection},
     hooks::install::hook,
     program::Program,
     rva::{
         ADD_PIXEL_SHADER_RVA, CB_FISHEYE_HOOK_RVA, GX_FFX_DRAW_CONTEXT_RVA, GX_FFX_DRAW_PASS_RVA,
         USES_DITHERING_RVA,
     },
+    shaders::{
+        distortion::FovCorrection,
+        payload::{
+            MAX_MARKERS, PAYLOAD_SLOTS, PayloadSlot, ShaderPayload, ShaderSettings, f16_round,
+        },
+    },
 };
 
+pub mod crosshair;
+pub mod distortion;
+pub mod payload;
 pub mod screen;
 
 static TONE_MAP_HOOK: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/ToneMap_PostHook.ppo"));
@@ -65,81 +80,251 @@ pub fn hook_shaders(program: Program) -> eyre::Result<()> {
     }
 }
 
-static SHADER_FLAGS: AtomicU32 = AtomicU32::new(0);
-static SHADER_PARAMS: AtomicU64 = AtomicU64::new(0);
-static SHADER_PARAMS2: AtomicU64 = AtomicU64::new(0);
+/// The settings being built for the next frame.
+static SHADER_SETTINGS_STAGING: Mutex<ShaderSettings> = Mutex::new(ShaderSettings::INITIAL);
+
+/// Snapshots of the settings, swapped once per frame by [`submit_shader_payload`].
+///
+/// Triple buffering keeps the snapshot read by the render thread intact unless it stalls
+/// for two whole frames while copying it.
+static SHADER_SNAPSHOTS: [SnapshotCell; 3] =
+    [const { SnapshotCell(UnsafeCell::new(ShaderSnapshot::INITIAL)) }; 3];
+
+/// The current snapshot, read by `fisheye_distortion_cb_hook`.
+static SHADER_SNAPSHOT: AtomicPtr<ShaderSnapshot> = AtomicPtr::new(SHADER_SNAPSHOTS[0].0.get());
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ShaderSnapshot {
+    settings: ShaderSettings,
+    /// The packed settings, copied into `cbToneMap`.
+    payload: ShaderPayload,
+    /// Whether any of the effects is enabled, forcing the shader on.
+    enabled: u32,
+}
+
+impl ShaderSnapshot {
+    const INITIAL: Self = Self {
+        settings: ShaderSettings::INITIAL,
+        payload: ShaderPayload {
+            flags: 0,
+            params: [0; 2],
+            markers: [0; 2],
+        },
+        enabled: 0,
+    };
+}
+
+#[repr(transparent)]
+struct SnapshotCell(UnsafeCell<ShaderSnapshot>);
+
+// SAFETY: only written by `submit_shader_payload` while not being the current snapshot.
+unsafe impl Sync for SnapshotCell {}
 
 pub fn enable_fov_correction(
     state: bool,
     strength: f32,
     cylindricity: f32,
-    use_barrel: bool,
+    projection: FovProjection,
     horizontal_fov: f32,
+    aspect_ratio: f32,
 ) {
-    let state = state && strength > 0.05;
+    let mut settings = SHADER_SETTINGS_STAGING.lock().unwrap();
 
-    set_shader_flag(state, 0);
-    set_shader_flag(use_barrel, 1);
+    settings.aspect_ratio = aspect_ratio;
 
-    if state {
-        let cylindricity = cylindricity.to_bits() as u64;
+    let state = state && strength > 0.05;
 
-        let strength_width_ratio = strength * f32::tan(horizontal_fov * 0.5);
-        let strength_width_ratio = strength_width_ratio.to_bits() as u64;
+    settings.fov_correction = state;
+    settings.fov_projection = projection as u32;
 
-        SHADER_PARAMS.store(
-            cylindricity | (strength_width_ratio << 32),
-            Ordering::Relaxed,
+    if state {
+        let correction = FovCorrection::new(
+            projection,
+            strength,
+            cylindricity,
+            horizontal_fov,
+            aspect_ratio,
         );
+
+        // Match the precision of the shader.
+        settings.fov_correction_params = correction.params.to_array().map(f16_round);
     }
 }
 
-pub fn set_crosshair(crosshair: CrosshairKind, scale: (f32, f32)) {
-    let _ = SHADER_FLAGS.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |value| {
-        Some(value & !0b11100 | (crosshair as u32 & 0b111) << 2)
-    });
+pub fn set_fov_filtering(sharpening: f32, bicubic: bool) {
+    let mut settings = SHADER_SETTINGS_STAGING.lock().unwrap();
 
-    let rscale_x = scale.0.recip().to_bits() as u64;
-    let rscale_y = scale.1.recip().to_bits() as u64;
+    settings.fov_sharpening = sharpening;
+    settings.fov_bicubic = bicubic;
+}
 
-    SHADER_PARAMS2.store(rscale_x | (rscale_y << 32), Ordering::Relaxed);
+pub fn set_chromatic_aberration(scale: f32) {
+    SHADER_SETTINGS_STAGING.lock().unwrap().chromatic_aberration = scale;
 }
 
-fn get_fov_correction() -> (f32, f32) {
-    let params = SHADER_PARAMS.load(Ordering::Relaxed);
+pub fn set_tunnel_vision(intensity: f32) {
+    SHADER_SETTINGS_STAGING.lock().unwrap().tunnel_vision = intensity;
+}
 
-    let cylindricity = f32::from_bits(params as u32);
-    let strength_width_ratio = f32::from_bits((params >> 32) as u32);
+pub fn set_threat_markers(angles: [f32; MAX_MARKERS], alphas: [f32; MAX_MARKERS]) {
+    let mut settings = SHADER_SETTINGS_STAGING.lock().unwrap();
 
-    (cylindricity, strength_width_ratio)
+    settings.threat_angles = angles;
+    settings.threat_alphas = alphas;
 }
 
+pub fn set_crosshair(
+    crosshair: CrosshairKind,
+    scale: (f32, f32),
+    style: &CrosshairStyle,
+    shape: &CrosshairShape,
+) {
+    let mut settings = SHADER_SETTINGS_STAGING.lock().unwrap();
+
+    settings.crosshair_kind = crosshair as u32;
+    settings.crosshair_blend = style.blend as u32;
+
+    settings.crosshair_scale = [scale.0, scale.1];
+
+    settings.crosshair_color = style.color;
+    settings.crosshair_outline_color = style.outline_color;
+    settings.crosshair_opacity = style.opacity;
+
+    settings.crosshair_dot_radius = shape.dot_radius;
+    settings.crosshair_line_length = shape.line_length;
+    settings.crosshair_line_gap = shape.line_gap;
+    settings.crosshair_line_thickness = shape.line_thickness;
+    settings.crosshair_ring_radius = shape.ring_radius;
+    settings.crosshair_outline_width = shape.outline_width;
+    settings.crosshair_t_style = shape.t_style;
+    settings.crosshair_rotation = shape.rotation;
+}
+
+/// Publishes the staged settings to the shader in a single swap.
+pub fn submit_shader_payload() {
+    let settings = *SHADER_SETTINGS_STAGING.lock().unwrap();
+
+    // Avoid forcing the shader on with none of the effects.
+    let enabled = settings.fov_correction
+        || settings.crosshair_kind != CrosshairKind::None as u32
+        || settings.chromatic_aberration != 1.0
+        || settings.tunnel_vision > 0.0
+        || settings.threat_alphas.iter().any(|&alpha| alpha > 0.0);
+
+    let snapshot = ShaderSnapshot {
+        settings,
+        payload: settings.pack(),
+        enabled: enabled as u32,
+    };
+
+    let current = SHADER_SNAPSHOT.load(Ordering::Acquire);
+
+    let next = SHADER_SNAPSHOTS
+        .iter()
+        .position(|snapshot| snapshot.0.get() == current)
+        .map_or(0, |index| (index + 1) % SHADER_SNAPSHOTS.len());
+
+    let next = SHADER_SNAPSHOTS[next].0.get();
+
+    unsafe {
+        next.write(snapshot);
+    }
+
+    SHADER_SNAPSHOT.store(next, Ordering::Release);
+}
+
+/// The settings most recently submitted to the shader.
+fn get_shader_settings() -> ShaderSettings {
+    unsafe { (*SHADER_SNAPSHOT.load(Ordering::Acquire)).settings }
+}
+
+/// The aspect ratio of the game's camera, matching `g_vCameraParam.x` in the shader.
+fn get_aspect_ratio() -> f32 {
+    get_shader_settings().aspect_ratio
+}
+
+fn get_fov_correction() -> Option<FovCorrection> {
+    let settings = get_shader_settings();
+
+    let projection = match settings.fov_projection {
+        1 => FovProjection::Barrel,
+        2 => FovProjection::Panini,
+        3 => FovProjection::Stereographic,
+        4 => FovProjection::Cylindrical,
+        _ => FovProjection::Fisheye,
+    };
+
+    settings.fov_correction.then(|| FovCorrection {
+        projection,
+        params: Vec2::from_array(settings.fov_correction_params),
+        aspect_ratio: settings.aspect_ratio,
+    })
+}
+
+/// Offset of `cbToneMap` from RBP in the function patched by `fisheye_distortion_cb_hook`.
+const CB_TONE_MAP: i32 = -0x50;
+
+/// Offsets of `g_vChromaticAberrationRG` and `g_vChromaticAberrationB` in `cbToneMap`.
+const CB_CHROMATIC_ABERRATION_RG: i32 = 0xe0;
+const CB_CHROMATIC_ABERRATION_B: i32 = 0xf0;
+
 unsafe fn hook_shader_cb(program: Program) -> eyre::Result<()> {
+    const FLAGS: &PayloadSlot = &PAYLOAD_SLOTS[0];
+    const PARAMS: &PayloadSlot = &PAYLOAD_SLOTS[1];
+    const MARKERS: &PayloadSlot = &PAYLOAD_SLOTS[2];
+
     #[unsafe(naked)]
     extern "C" fn fisheye_distortion_cb_hook() {
         naked_asm! {
+            // Copy the current payload into the padding of the constant buffer
+            // (see `PAYLOAD_SLOTS` and "shaders/ToneMap_PostHook.hlsl").
+            // RCX, RDX and R8 are free until they are loaded below.
+            "mov rax,[rip+{snapshot}]",
+            "mov ecx,[rax+{payload}+{flags}]",
+            "mov [rbp+{cb_flags}],ecx",
+            "mov rcx,[rax+{payload}+{params}]",
+            "mov [rbp+{cb_params}],rcx",
+            "mov rcx,[rax+{payload}+{markers}]",
+            "mov [rbp+{cb_markers}],rcx",
+            // Scale the game's chromatic aberration.
+            "sub rsp,0x20",
+            "movups [rsp],xmm0",
+            "movups [rsp+0x10],xmm1",
+            "movss xmm0,[rax+{chromatic_aberration}]",
+            "shufps xmm0,xmm0,0",
+            "movups xmm1,[rbp+{cb_chromatic_aberration_rg}]",
+            "mulps xmm1,xmm0",
+            "movups [rbp+{cb_chromatic_aberration_rg}],xmm1",
+            "movsd xmm1,[rbp+{cb_chromatic_aberration_b}]",
+            "mulps xmm1,xmm0",
+            "movsd [rbp+{cb_chromatic_aberration_b}],xmm1",
+            "movups xmm0,[rsp]",
+            "movups xmm1,[rsp+0x10]",
+            "add rsp,0x20",
+            // Force the shader on.
+            "mov eax,[rax+{enabled}]",
+            "test eax,eax",
+            "setne al",
+            "mov [r15+0xcb0],al",
             // Original code start...
             "mov r8,[rsp+0x78]",
             "lea rdx,[rbp-0x80]",
             "mov rcx,[r14+0x08]",
             // ...original code end.
-            // Forward the flags to the constant buffer (see "shaders/ToneMap_PostHook.hlsl").
-            "mov eax,[rip+{}]",
-            "mov [rbp-0x44],eax",
-            // Force the shader on.
-            "test eax,eax",
-            "setne al",
-            "mov [r15+0xcb0],al",
-            // Forward the screen width ratio to the shader (see above).
-            "mov rax,[rip+{}]",
-            "mov [rbp+0xa8],rax",
-            // Forward the crosshair size.
-            "mov rax,[rip+{}]",
-            "mov [rbp+0x148],rax",
             "ret",
-            sym SHADER_FLAGS,
-            sym SHADER_PARAMS,
-            sym SHADER_PARAMS2,
+            snapshot = sym SHADER_SNAPSHOT,
+            payload = const offset_of!(ShaderSnapshot, payload),
+            flags = const FLAGS.offset,
+            params = const PARAMS.offset,
+            markers = const MARKERS.offset,
+            cb_flags = const CB_TONE_MAP + FLAGS.cb_offset as i32,
+            cb_params = const CB_TONE_MAP + PARAMS.cb_offset as i32,
+            cb_markers = const CB_TONE_MAP + MARKERS.cb_offset as i32,
+            chromatic_aberration = const offset_of!(ShaderSnapshot, settings.chromatic_aberration),
+            cb_chromatic_aberration_rg = const CB_TONE_MAP + CB_CHROMATIC_ABERRATION_RG,
+            cb_chromatic_aberration_b = const CB_TONE_MAP + CB_CHROMATIC_ABERRATION_B,
+            enabled = const offset_of!(ShaderSnapshot, enabled),
         }
     }
 
@@ -221,15 +406,3 @@ static ENABLE_DITHERING: AtomicBool = AtomicBool::new(true);
 pub fn enable_dithering(state: bool) {
     ENABLE_DITHERING.store(state, Ordering::Relaxed);
 }
-
-fn set_shader_flag(state: bool, pos: u32) -> u32 {
-    let flag = 1 << pos;
-    match state {
-        true => SHADER_FLAGS.fetch_or(flag, Ordering::Relaxed),
-        false => SHADER_FLAGS.fetch_and(!flag, Ordering::Relaxed),
-    }
-}
-
-fn get_shader_flag(pos: u32) -> bool {
-    (SHADER_FLAGS.load(Ordering::Relaxed) >> pos) & 1 != 0
-}
@@ -0,0 +1,418 @@
+//! CPU reference implementation of `CrosshairCoverage` and `CrosshairBlend`
+//! (see "shaders/ToneMap_PostHook.hlsl").
+
+use std::f32::consts::TAU;
+
+use glam::{UVec2, Vec2, Vec3};
+
+use crate::config::{CrosshairBlend, CrosshairKind, CrosshairShape, CrosshairStyle};
+
+pub struct CrosshairRasterizer {
+    pub kind: CrosshairKind,
+    pub scale: (f32, f32),
+    pub style: CrosshairStyle,
+    pub shape: CrosshairShape,
+    pub screen_size: Vec2,
+}
+
+impl CrosshairRasterizer {
+    /// Fill and outline coverage of a `size` window of pixels in the center of the screen.
+    pub fn rasterize(&self, size: UVec2) -> Vec<Vec2> {
+        let origin = ((self.screen_size - size.as_vec2()) * 0.5).floor();
+
+        (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x as f32, y as f32)))
+            .map(|offset| self.pixel(origin + offset))
+            .collect()
+    }
+
+    /// Fill and outline coverage of the pixel at `position` (see `CrosshairCoverage`).
+    pub fn pixel(&self, position: Vec2) -> Vec2 {
+        let mut alpha = self.alpha(position);
+
+        if self.style.blend == CrosshairBlend::SolidOutline && self.kind != CrosshairKind::Custom {
+            let dilated = [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y]
+                .map(|offset| self.alpha(position + offset).x)
+                .into_iter()
+                .fold(0.0, f32::max);
+
+            alpha.y = (dilated - alpha.x).max(0.0);
+        }
+
+        alpha
+    }
+
+    /// Blends the crosshair over the scene color (see `CrosshairBlend`).
+    pub fn blend(&self, rgb: Vec3, alpha: Vec2) -> Vec3 {
+        let opacity = self.style.opacity;
+
+        let fill_color = match self.style.blend {
+            CrosshairBlend::Invert => 1.0 - rgb,
+            _ => Vec3::from_array(self.style.color),
+        };
+
+        let outline = match self.style.blend {
+            CrosshairBlend::SolidOutline => alpha.y,
+            _ => 0.0,
+        };
+
+        let outline_color = Vec3::from_array(self.style.outline_color);
+
+        let rgb = rgb.lerp(outline_color, outline * opacity);
+        rgb.lerp(fill_color, alpha.x * opacity)
+    }
+
+    /// Fill and outline coverage of the pixel at `position` (see `CrosshairAlpha`).
+    ///
+    /// `fwidth` is approximated with forward differences to the neighboring pixels.
+    fn alpha(&self, position: Vec2) -> Vec2 {
+        let field = |position| self.field(self.screen_coords(position));
+
+        let r = field(position);
+        let d = (field(position + Vec2::X) - r).abs() + (field(position + Vec2::Y) - r).abs();
+
+        self.coverage(self.screen_coords(position), r, d)
+    }
+
+    fn screen_coords(&self, position: Vec2) -> Vec2 {
+        let uv = (position + 0.5) / self.screen_size;
+        let aspect_ratio = self.screen_size.x / self.screen_size.y;
+        let scale_reciprocal = Vec2::new(self.scale.0.recip(), self.scale.1.recip());
+
+        (uv - 0.5) * scale_reciprocal * Vec2::new(aspect_ratio, 1.0)
+    }
+
+    /// The values the shader takes `fwidth` of.
+    fn field(&self, c: Vec2) -> Vec3 {
+        match self.kind {
+            CrosshairKind::None | CrosshairKind::Cross => Vec3::ZERO,
+            CrosshairKind::Dot => Vec3::splat(c.length() / 0.0018),
+            CrosshairKind::Circle => Vec3::splat(c.length()) / Vec3::new(0.0066, 0.008, 1.0),
+            CrosshairKind::CircleDot => Vec3::splat(c.length()) / Vec3::new(0.0066, 0.008, 0.0012),
+            CrosshairKind::Angled => Vec3::splat(c.x.abs() + c.y.abs() * 3.0),
+            CrosshairKind::Custom => c.extend(0.0),
+        }
+    }
+
+    /// Signed distance to the custom crosshair (see `CustomCrosshairAlpha`).
+    fn custom_distance(&self, c: Vec2) -> f32 {
+        const PX: f32 = 1.0 / 1080.0;
+
+        let shape = &self.shape;
+
+        let dot_radius = shape.dot_radius as f32 * PX;
+        let line_length = shape.line_length as f32 * PX;
+        let line_gap = shape.line_gap as f32 * PX;
+        let half_thickness = shape.line_thickness as f32 * PX * 0.5;
+        let ring_radius = shape.ring_radius as f32 * PX;
+
+        let (s, co) = (shape.rotation as f32 * TAU / 8.0).sin_cos();
+        let c = Vec2::new(c.x * co + c.y * s, c.y * co - c.x * s);
+
+        let mut dist = 1.0f32;
+
+        if dot_radius > 0.0 {
+            dist = dist.min(c.length() - dot_radius);
+        }
+
+        if line_length > 0.0 {
+            let half_size = Vec2::new(line_length * 0.5, half_thickness);
+            let a = c.abs();
+            let center = line_gap + half_size.x;
+
+            let horizontal = sd_box(Vec2::new(a.x - center, c.y), half_size);
+            let mut vertical = sd_box(Vec2::new(a.y - center, c.x), half_size);
+
+            if shape.t_style && c.y < 0.0 {
+                vertical = 1.0;
+            }
+
+            dist = dist.min(horizontal.min(vertical));
+        }
+
+        if ring_radius > 0.0 {
+            dist = dist.min((c.length() - ring_radius).abs() - half_thickness);
+        }
+
+        dist
+    }
+
+    fn coverage(&self, c: Vec2, r: Vec3, d: Vec3) -> Vec2 {
+        let disc = |r: f32, d: f32| 1.0 - smoothstep(1.0 - d, 1.0 + d, r);
+
+        let fill = match self.kind {
+            CrosshairKind::None => 0.0,
+            CrosshairKind::Cross => {
+                let c = c.abs();
+                let is_inside =
+                    c.cmplt(Vec2::splat(0.0013)).any() && c.cmplt(Vec2::splat(0.007)).all();
+                if is_inside { 1.0 } else { 0.0 }
+            }
+            CrosshairKind::Dot => disc(r.x, d.x),
+            CrosshairKind::Circle => disc(r.y, d.y) - disc(r.x, d.x),
+            CrosshairKind::CircleDot => disc(r.y, d.y) - disc(r.x, d.x) + disc(r.z, d.z),
+            CrosshairKind::Angled => {
+                let a = smoothstep(0.015 - d.x, 0.0175, r.x) - smoothstep(0.0175, 0.02 + d.x, r.x);
+                if c.x.abs() > 0.007 { a } else { 0.0 }
+            }
+            CrosshairKind::Custom => {
+                let outline_width = self.shape.outline_width as f32 / 1080.0;
+
+                let dist = self.custom_distance(c);
+                let d = d.x.max(d.y) * 0.5;
+
+                let fill = 1.0 - smoothstep(-d, d, dist);
+                let outlined = 1.0 - smoothstep(-d, d, dist - outline_width);
+
+                return Vec2::new(fill, (outlined - fill).max(0.0));
+            }
+        };
+
+        Vec2::new(fill, 0.0)
+    }
+}
+
+/// Signed distance to a box centered on the origin.
+fn sd_box(p: Vec2, half_size: Vec2) -> f32 {
+    let d = p.abs() - half_size;
+    d.max(Vec2::ZERO).length() + d.x.max(d.y).min(0.0)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec2, Vec2, Vec3};
+
+    use crate::config::{CrosshairBlend, CrosshairKind, CrosshairShape, CrosshairStyle};
+
+    use super::CrosshairRasterizer;
+
+    const SIZE: UVec2 = UVec2::new(23, 23);
+
+    const STYLE: CrosshairStyle = CrosshairStyle {
+        color: [1.0, 0.0, 0.0],
+        opacity: 1.0,
+        outline_color: [0.0, 0.0, 1.0],
+        blend: CrosshairBlend::Invert,
+    };
+
+    fn rasterizer(kind: CrosshairKind) -> CrosshairRasterizer {
+        CrosshairRasterizer {
+            kind,
+            scale: (1.0, 1.0),
+            style: STYLE,
+            shape: CrosshairShape::default(),
+            screen_size: Vec2::new(1920.0, 1080.0),
+        }
+    }
+
+    fn render(rasterizer: &CrosshairRasterizer) -> String {
+        let pixels = rasterizer.rasterize(SIZE);
+
+        pixels
+            .chunks(SIZE.x as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|coverage| match (coverage.x, coverage.y) {
+                        (fill, _) if fill >= 0.5 => '#',
+                        (fill, _) if fill >= 0.05 => '+',
+                        (_, outline) if outline >= 0.5 => 'o',
+                        _ => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn check_golden(rasterizer: &CrosshairRasterizer, golden: &str) {
+        let rendered = render(rasterizer);
+        assert_eq!(rendered, golden.trim(), "\n{rendered}\n");
+    }
+
+    #[test]
+    fn golden_dot() {
+        let rasterizer = rasterizer(CrosshairKind::Dot);
+        check_golden(&rasterizer, GOLDEN_DOT);
+    }
+
+    #[test]
+    fn golden_cross() {
+        let rasterizer = rasterizer(CrosshairKind::Cross);
+        check_golden(&rasterizer, GOLDEN_CROSS);
+    }
+
+    #[test]
+    fn golden_cross_solid_outline() {
+        let rasterizer = CrosshairRasterizer {
+            style: CrosshairStyle {
+                blend: CrosshairBlend::SolidOutline,
+                ..STYLE
+            },
+            ..rasterizer(CrosshairKind::Cross)
+        };
+        check_golden(&rasterizer, GOLDEN_CROSS_SOLID_OUTLINE);
+    }
+
+    #[test]
+    fn golden_custom() {
+        let rasterizer = CrosshairRasterizer {
+            style: CrosshairStyle {
+                blend: CrosshairBlend::SolidOutline,
+                ..STYLE
+            },
+            shape: CrosshairShape {
+                dot_radius: 1,
+                line_length: 5,
+                line_gap: 3,
+                line_thickness: 2,
+                t_style: true,
+                ring_radius: 0,
+                outline_width: 1,
+                rotation: 0,
+            },
+            ..rasterizer(CrosshairKind::Custom)
+        };
+        check_golden(&rasterizer, GOLDEN_CUSTOM);
+    }
+
+    #[test]
+    fn blend_modes() {
+        let gray = Vec3::splat(0.5);
+        let alpha = Vec2::new(1.0, 0.0);
+        let outline = Vec2::new(0.0, 1.0);
+
+        let blend = |blend, opacity, alpha| {
+            let rasterizer = CrosshairRasterizer {
+                style: CrosshairStyle {
+                    blend,
+                    opacity,
+                    ..STYLE
+                },
+                ..rasterizer(CrosshairKind::Dot)
+            };
+            rasterizer.blend(gray, alpha)
+        };
+
+        // Inverting mid-gray is invisible, solid colors are not.
+        assert_eq!(blend(CrosshairBlend::Invert, 1.0, alpha), gray);
+        assert_eq!(blend(CrosshairBlend::Solid, 1.0, alpha), Vec3::X);
+        assert_eq!(
+            blend(CrosshairBlend::Solid, 0.5, alpha),
+            Vec3::new(0.75, 0.25, 0.25)
+        );
+
+        // Only "solid_outline" draws the outline, in outline_color.
+        assert_eq!(blend(CrosshairBlend::Solid, 1.0, outline), gray);
+        assert_eq!(blend(CrosshairBlend::Invert, 1.0, outline), gray);
+        assert_eq!(blend(CrosshairBlend::SolidOutline, 1.0, outline), Vec3::Z);
+    }
+
+    const GOLDEN_DOT: &str = r"
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+...........+++.........
+..........+##+.........
+.........+####+........
+.........+####+........
+.........++##++........
+...........+++.........
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+";
+
+    const GOLDEN_CROSS: &str = r"
+.......................
+.......................
+.......................
+.......................
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+....################...
+....################...
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+...........##..........
+.......................
+.......................
+.......................
+";
+
+    const GOLDEN_CROSS_SOLID_OUTLINE: &str = r"
+.......................
+.......................
+.......................
+...........oo..........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+....ooooooo##ooooooo...
+...o################o..
+...o################o..
+....ooooooo##ooooooo...
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+...........oo..........
+.......................
+.......................
+";
+
+    const GOLDEN_CUSTOM: &str = r"
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+.......................
+...ooooooo.oo.ooooooo..
+...o#####oo##oo#####o..
+...o#####oo##oo#####o..
+...ooooooo.oo.ooooooo..
+..........oooo.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........o##o.........
+..........oooo.........
+.......................
+.......................
+";
+}
@@ -0,0 +1,199 @@
+//! CPU reference implementation of the FOV correction (`MapUvFisheye`, `MapUvBarrel`, etc.)
+//! in "shaders/ToneMap_PostHook.hlsl".
+
+use glam::Vec2;
+
+use crate::config::FovProjection;
+
+/// FOV correction parameters, as forwarded to the shader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FovCorrection {
+    pub projection: FovProjection,
+    /// `g_ErfpsCorrectParam`.
+    pub params: Vec2,
+    /// `g_vCameraParam.x`.
+    pub aspect_ratio: f32,
+}
+
+impl FovCorrection {
+    pub fn new(
+        projection: FovProjection,
+        strength: f32,
+        cylindricity: f32,
+        horizontal_fov: f32,
+        aspect_ratio: f32,
+    ) -> Self {
+        let half_width = f32::tan(horizontal_fov * 0.5);
+
+        // Fisheye and barrel take the scaled strength, the other projections
+        // take the strength and the tangent of the half FOV.
+        let params = match projection {
+            FovProjection::Fisheye | FovProjection::Barrel => {
+                Vec2::new(cylindricity, strength * half_width)
+            }
+            _ => Vec2::new(strength, half_width),
+        };
+
+        Self {
+            projection,
+            params,
+            aspect_ratio,
+        }
+    }
+
+    /// Maps corrected screen coordinates to the game's screen coordinates.
+    pub fn map_uv(&self, uv: Vec2) -> Vec2 {
+        match self.projection {
+            FovProjection::Fisheye => self.map_uv_fisheye(uv),
+            FovProjection::Barrel => self.map_uv_barrel(uv),
+            FovProjection::Panini => self.map_uv_panini(uv),
+            FovProjection::Stereographic => self.map_uv_stereographic(uv),
+            FovProjection::Cylindrical => self.map_uv_cylindrical(uv),
+        }
+    }
+
+    /// Local magnification of the correction, matching `Magnification`.
+    pub fn magnification(&self, uv: Vec2) -> f32 {
+        const E: f32 = 1.0 / 1024.0;
+
+        let mapped = self.map_uv(uv);
+
+        let dx = (self.map_uv(uv + Vec2::new(E, 0.0)) - mapped) / E;
+        let dy = (self.map_uv(uv + Vec2::new(0.0, E)) - mapped) / E;
+
+        dx.perp_dot(dy).abs().max(1e-6).sqrt().recip()
+    }
+
+    fn map_uv_fisheye(&self, uv: Vec2) -> Vec2 {
+        let c = uv - 0.5;
+
+        let r2 = c.x * c.x;
+        let strength = self.params.y;
+
+        let f = 1.0 + strength * r2.sqrt() * r2;
+        let f_max = 1.0 + strength * 0.125;
+
+        c * f / f_max + 0.5
+    }
+
+    fn map_uv_barrel(&self, uv: Vec2) -> Vec2 {
+        let aspect_ratio = self.aspect_ratio;
+        let cylindrical_ratio = self.params.x;
+
+        // `g_vCameraParam.y` is the reciprocal of the aspect ratio.
+        let scaled_height = self.params.y / aspect_ratio;
+        let cyl_aspect_ratio = aspect_ratio * cylindrical_ratio;
+        let aspect_diag_sq = aspect_ratio * aspect_ratio + 1.0;
+        let diag_sq = scaled_height * scaled_height * aspect_diag_sq;
+        let signed_uv = 2.0 * uv - 1.0;
+
+        let z = 0.5 * (diag_sq + 1.0).sqrt() + 0.5;
+        let ny = (z - 1.0) / (cyl_aspect_ratio * cyl_aspect_ratio + 1.0);
+
+        let uv_dot = ny.sqrt() * Vec2::new(cyl_aspect_ratio, 1.0) * signed_uv;
+        let uv_z = (z - 1.0) * 0.5 + uv;
+
+        let uv_dot_sq = uv_dot.dot(uv_dot);
+        (uv_z - uv_dot_sq * 0.5) / (z - uv_dot_sq)
+    }
+
+    fn map_uv_panini(&self, uv: Vec2) -> Vec2 {
+        let d = self.params.x;
+        let half_fov = self.params.y.atan();
+
+        let p = self.projection_coords(uv, perspective_radius(half_fov.cos(), d));
+
+        let c = perspective_cos(p.x.abs(), d);
+        let s = (d + 1.0) / (d + c);
+
+        let rect = Vec2::new((1.0 - c * c).sqrt().copysign(p.x), p.y / s) / c;
+        self.rectilinear_uv(rect)
+    }
+
+    fn map_uv_stereographic(&self, uv: Vec2) -> Vec2 {
+        let d = self.params.x;
+        let half_fov = self.params.y.atan();
+
+        let p = self.projection_coords(uv, perspective_radius(half_fov.cos(), d));
+
+        let r = p.length();
+        if r < 1e-6 {
+            return uv;
+        }
+
+        let c = perspective_cos(r, d);
+
+        let rect = p * (1.0 - c * c).sqrt() / (c * r);
+        self.rectilinear_uv(rect)
+    }
+
+    fn map_uv_cylindrical(&self, uv: Vec2) -> Vec2 {
+        let k = (1.0 - self.params.x).max(1e-3);
+        let half_fov = self.params.y.atan();
+
+        let p = self.projection_coords(uv, (k * half_fov).tan() / k);
+
+        let longitude = (k * p.x).atan() / k;
+        let c = longitude.cos();
+
+        let rect = Vec2::new(longitude.tan(), p.y * c.powf(k) / c);
+        self.rectilinear_uv(rect)
+    }
+
+    /// See `ProjectionCoords`.
+    fn projection_coords(&self, uv: Vec2, edge: f32) -> Vec2 {
+        (uv * 2.0 - 1.0) * Vec2::new(1.0, self.aspect_ratio.recip()) * edge
+    }
+
+    /// See `RectilinearUv`.
+    fn rectilinear_uv(&self, rect: Vec2) -> Vec2 {
+        let half_width = self.params.y;
+        rect / (Vec2::new(1.0, self.aspect_ratio.recip()) * half_width) * 0.5 + 0.5
+    }
+}
+
+/// See `PerspectiveRadius`.
+pub fn perspective_radius(cos_angle: f32, d: f32) -> f32 {
+    (d + 1.0) * (1.0 - cos_angle * cos_angle).sqrt() / (d + cos_angle)
+}
+
+/// See `PerspectiveCos`.
+pub fn perspective_cos(r: f32, d: f32) -> f32 {
+    let r2 = r * r;
+    let a = r2 + (d + 1.0) * (d + 1.0);
+    let disc = r2 * r2 * d * d - a * (r2 * d * d - (d + 1.0) * (d + 1.0));
+    (disc.max(0.0).sqrt() - r2 * d) / a
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use crate::{config::FovProjection, shaders::distortion::FovCorrection};
+
+    #[test]
+    fn correction_magnifies_center() {
+        let center = Vec2::splat(0.5);
+
+        let projections = [
+            FovProjection::Fisheye,
+            FovProjection::Barrel,
+            FovProjection::Panini,
+            FovProjection::Stereographic,
+            FovProjection::Cylindrical,
+        ];
+
+        for projection in projections {
+            for aspect_ratio in [16.0 / 10.0, 16.0 / 9.0, 21.0 / 9.0, 32.0 / 9.0] {
+                let correction =
+                    FovCorrection::new(projection, 0.5, 2.0, 110f32.to_radians(), aspect_ratio);
+
+                assert!(
+                    correction.magnification(center) > 1.01,
+                    "{correction:?}: {}",
+                    correction.magnification(center)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,632 @@
+//! Layout of the settings forwarded to "shaders/ToneMap_PostHook.hlsl".
+//!
+//! The game uploads exactly `cbToneMap`, so the settings are bit packed into the padding
+//! at the end of three of its registers (see [`PAYLOAD_SLOTS`]).
+//!
+//! Also included by "build.rs", which generates the matching HLSL declarations.
+
+use std::{f32::consts::TAU, iter, mem::offset_of};
+
+/// Bumped on every change to the layout of [`ShaderPayload`].
+pub const PAYLOAD_VERSION: u32 = 12;
+
+/// Size of `cbToneMap`, as uploaded by the game.
+pub const CB_TONE_MAP_SIZE: usize = 0x1a0;
+
+/// Number of threat markers packed into a word.
+pub const MAX_MARKERS: usize = 3;
+
+/// Crosshair scales are packed as `log2(scale)`, in steps of 1/30 from 1/32x.
+pub const CROSSHAIR_SCALE_LOG2_MIN: f32 = -5.0;
+pub const CROSSHAIR_SCALE_STEPS: f32 = 30.0;
+
+/// Padding of `cbToneMap` holding part of the [`ShaderPayload`].
+pub struct PayloadSlot {
+    pub name: &'static str,
+    pub hlsl_type: &'static str,
+    /// The member of `cbToneMap` padded by the slot.
+    pub after: &'static str,
+    /// Offset in `cbToneMap`.
+    pub cb_offset: usize,
+    /// Offset in [`ShaderPayload`].
+    pub offset: usize,
+    pub size: usize,
+}
+
+pub const PAYLOAD_SLOTS: [PayloadSlot; 3] = [
+    PayloadSlot {
+        name: "g_ErfpsFlags",
+        hlsl_type: "uint",
+        after: "float3 g_ToneMapInvSceneLumScale",
+        cb_offset: 0x0c,
+        offset: offset_of!(ShaderPayload, flags),
+        size: 4,
+    },
+    PayloadSlot {
+        name: "g_ErfpsParams",
+        hlsl_type: "uint2",
+        after: "float2 g_vChromaticAberrationB",
+        cb_offset: 0xf8,
+        offset: offset_of!(ShaderPayload, params),
+        size: 8,
+    },
+    PayloadSlot {
+        name: "g_ErfpsMarkers",
+        hlsl_type: "uint2",
+        after: "float2 g_vCameraParam",
+        cb_offset: 0x198,
+        offset: offset_of!(ShaderPayload, markers),
+        size: 8,
+    },
+];
+
+// The slots hold the whole payload, each one filling the rest of a register of `cbToneMap`.
+const _: () = {
+    let mut size = 0;
+    let mut i = 0;
+
+    while i < PAYLOAD_SLOTS.len() {
+        let slot = &PAYLOAD_SLOTS[i];
+
+        assert!(slot.offset == size);
+        assert!(slot.cb_offset % 16 + slot.size == 16);
+        assert!(slot.cb_offset + slot.size <= CB_TONE_MAP_SIZE);
+
+        size += slot.size;
+        i += 1;
+    }
+
+    assert!(size == size_of::<ShaderPayload>());
+    assert!(PAYLOAD_VERSION <= ShaderPayload::VERSION.max());
+    assert!(MAX_MARKERS as u32 * ShaderPayload::MARKER_STRIDE <= u32::BITS);
+};
+
+/// A bit field of a payload word, unpacked by `ErfpsBits` in the shader.
+#[derive(Clone, Copy, Debug)]
+pub struct BitField {
+    pub name: &'static str,
+    pub offset: u32,
+    pub bits: u32,
+}
+
+impl BitField {
+    const fn new(name: &'static str, offset: u32, bits: u32) -> Self {
+        Self { name, offset, bits }
+    }
+
+    pub const fn max(self) -> u32 {
+        (1 << self.bits) - 1
+    }
+
+    pub const fn shifted(self, shift: u32) -> Self {
+        Self {
+            offset: self.offset + shift,
+            ..self
+        }
+    }
+
+    pub fn pack(self, value: u32) -> u32 {
+        value.min(self.max()) << self.offset
+    }
+
+    /// Packs a value from 0.0 to 1.0.
+    pub fn pack_unorm(self, value: f32) -> u32 {
+        self.pack((value.clamp(0.0, 1.0) * self.max() as f32).round() as u32)
+    }
+
+    pub fn unpack(self, word: u32) -> u32 {
+        (word >> self.offset) & self.max()
+    }
+
+    pub fn unpack_unorm(self, word: u32) -> f32 {
+        self.unpack(word) as f32 / self.max() as f32
+    }
+}
+
+/// Settings packed into the padding of `cbToneMap`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShaderPayload {
+    /// `g_ErfpsFlags`.
+    pub flags: u32,
+    /// `g_ErfpsParams`, the FOV correction parameters and the crosshair.
+    pub params: [u32; 2],
+    /// `g_ErfpsMarkers`, the threat markers in `x`, the custom crosshair shape and the
+    /// crosshair outline color in `y`.
+    pub markers: [u32; 2],
+}
+
+impl ShaderPayload {
+    // `g_ErfpsFlags`.
+    pub const VERSION: BitField = BitField::new("VERSION", 0, 4);
+    pub const FOV_CORRECTION: BitField = BitField::new("FOV_CORRECTION", 4, 1);
+    pub const FOV_PROJECTION: BitField = BitField::new("FOV_PROJECTION", 5, 3);
+    pub const FOV_BICUBIC: BitField = BitField::new("FOV_BICUBIC", 8, 1);
+    pub const FOV_SHARPENING: BitField = BitField::new("FOV_SHARPENING", 9, 4);
+    pub const CROSSHAIR_KIND: BitField = BitField::new("CROSSHAIR_KIND", 13, 3);
+    pub const CROSSHAIR_BLEND: BitField = BitField::new("CROSSHAIR_BLEND", 16, 2);
+    pub const CROSSHAIR_OPACITY: BitField = BitField::new("CROSSHAIR_OPACITY", 18, 4);
+    pub const TUNNEL_VISION: BitField = BitField::new("TUNNEL_VISION", 22, 4);
+    pub const CROSSHAIR_T_STYLE: BitField = BitField::new("CROSSHAIR_T_STYLE", 26, 1);
+    pub const CROSSHAIR_ROTATION: BitField = BitField::new("CROSSHAIR_ROTATION", 27, 3);
+    pub const CROSSHAIR_OUTLINE: BitField = BitField::new("CROSSHAIR_OUTLINE", 30, 2);
+
+    // `g_ErfpsParams.x`, the FOV correction parameters as half floats.
+    pub const FOV_CORRECTION_X: BitField = BitField::new("FOV_CORRECTION_X", 0, 16);
+    pub const FOV_CORRECTION_Y: BitField = BitField::new("FOV_CORRECTION_Y", 16, 16);
+
+    // `g_ErfpsParams.y`.
+    pub const CROSSHAIR_SCALE_X: BitField = BitField::new("CROSSHAIR_SCALE_X", 0, 8);
+    pub const CROSSHAIR_SCALE_Y: BitField = BitField::new("CROSSHAIR_SCALE_Y", 8, 8);
+    pub const CROSSHAIR_RED: BitField = BitField::new("CROSSHAIR_RED", 16, 5);
+    pub const CROSSHAIR_GREEN: BitField = BitField::new("CROSSHAIR_GREEN", 21, 6);
+    pub const CROSSHAIR_BLUE: BitField = BitField::new("CROSSHAIR_BLUE", 27, 5);
+
+    // A threat marker of `g_ErfpsMarkers.x`, repeated every `MARKER_STRIDE` bits.
+    pub const MARKER_ANGLE: BitField = BitField::new("MARKER_ANGLE", 0, 6);
+    pub const MARKER_ALPHA: BitField = BitField::new("MARKER_ALPHA", 6, 4);
+    pub const MARKER_STRIDE: u32 = 10;
+
+    // `g_ErfpsMarkers.y`, the custom crosshair sizes in pixels at 1080p and the outline color.
+    pub const CROSSHAIR_DOT: BitField = BitField::new("CROSSHAIR_DOT", 0, 2);
+    pub const CROSSHAIR_LENGTH: BitField = BitField::new("CROSSHAIR_LENGTH", 2, 4);
+    pub const CROSSHAIR_GAP: BitField = BitField::new("CROSSHAIR_GAP", 6, 4);
+    /// Packed as the thickness minus one pixel.
+    pub const CROSSHAIR_THICKNESS: BitField = BitField::new("CROSSHAIR_THICKNESS", 10, 2);
+    pub const CROSSHAIR_RING: BitField = BitField::new("CROSSHAIR_RING", 12, 4);
+    pub const OUTLINE_RED: BitField = BitField::new("OUTLINE_RED", 16, 5);
+    pub const OUTLINE_GREEN: BitField = BitField::new("OUTLINE_GREEN", 21, 6);
+    pub const OUTLINE_BLUE: BitField = BitField::new("OUTLINE_BLUE", 27, 5);
+
+    /// Bit fields of each word, with the size of the word.
+    pub const WORDS: [(&[BitField], u32); 5] = [
+        (
+            &[
+                Self::VERSION,
+                Self::FOV_CORRECTION,
+                Self::FOV_PROJECTION,
+                Self::FOV_BICUBIC,
+                Self::FOV_SHARPENING,
+                Self::CROSSHAIR_KIND,
+                Self::CROSSHAIR_BLEND,
+                Self::CROSSHAIR_OPACITY,
+                Self::TUNNEL_VISION,
+                Self::CROSSHAIR_T_STYLE,
+                Self::CROSSHAIR_ROTATION,
+                Self::CROSSHAIR_OUTLINE,
+            ],
+            u32::BITS,
+        ),
+        (&[Self::FOV_CORRECTION_X, Self::FOV_CORRECTION_Y], u32::BITS),
+        (
+            &[
+                Self::CROSSHAIR_SCALE_X,
+                Self::CROSSHAIR_SCALE_Y,
+                Self::CROSSHAIR_RED,
+                Self::CROSSHAIR_GREEN,
+                Self::CROSSHAIR_BLUE,
+            ],
+            u32::BITS,
+        ),
+        (
+            &[Self::MARKER_ANGLE, Self::MARKER_ALPHA],
+            Self::MARKER_STRIDE,
+        ),
+        (
+            &[
+                Self::CROSSHAIR_DOT,
+                Self::CROSSHAIR_LENGTH,
+                Self::CROSSHAIR_GAP,
+                Self::CROSSHAIR_THICKNESS,
+                Self::CROSSHAIR_RING,
+                Self::OUTLINE_RED,
+                Self::OUTLINE_GREEN,
+                Self::OUTLINE_BLUE,
+            ],
+            u32::BITS,
+        ),
+    ];
+
+    /// HLSL definitions of the bit fields, with the `ErfpsBits` and `ErfpsUnorm` helpers.
+    pub fn hlsl_declaration() -> String {
+        let mut hlsl = format!(
+            "// Generated from \"src/shaders/payload.rs\", do not edit.\n\n\
+             #define ERFPS_PAYLOAD_VERSION {PAYLOAD_VERSION}\n\
+             #define ERFPS_MAX_MARKERS {MAX_MARKERS}\n\
+             #define ERFPS_MARKER_STRIDE {}\n\
+             #define ERFPS_MARKER_ANGLE_STEPS {}\n\
+             #define ERFPS_CROSSHAIR_SCALE_LOG2_MIN {CROSSHAIR_SCALE_LOG2_MIN:?}\n\
+             #define ERFPS_CROSSHAIR_SCALE_STEPS {CROSSHAIR_SCALE_STEPS:?}\n\n",
+            Self::MARKER_STRIDE,
+            Self::MARKER_ANGLE.max() + 1,
+        );
+
+        for (fields, _) in Self::WORDS {
+            for field in fields {
+                hlsl += &format!(
+                    "#define ERFPS_{} {}, {}\n",
+                    field.name, field.offset, field.bits
+                );
+            }
+        }
+
+        hlsl += "\n\
+            // Unpacks a bit field, e.g. `ErfpsBits(g_ErfpsFlags, ERFPS_FOV_PROJECTION)`.\n\
+            uint ErfpsBits(uint word, uint offset, uint bits)\n\
+            {\n    return (word >> offset) & ((1u << bits) - 1u);\n}\n\n\
+            // Unpacks a bit field holding a value from 0.0 to 1.0.\n\
+            float ErfpsUnorm(uint word, uint offset, uint bits)\n\
+            {\n    return float(ErfpsBits(word, offset, bits)) / float((1u << bits) - 1u);\n}\n";
+
+        hlsl
+    }
+}
+
+/// Settings forwarded to the shader, packed by [`ShaderSettings::pack`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShaderSettings {
+    pub fov_correction: bool,
+    /// `FovProjection` of the FOV correction.
+    pub fov_projection: u32,
+    /// FOV correction parameters (see `FovCorrection`), rounded with [`f16_round`].
+    pub fov_correction_params: [f32; 2],
+    /// The aspect ratio of the game's camera, matching `g_vCameraParam.x`.
+    pub aspect_ratio: f32,
+    /// Whether the FOV correction resamples the scene with bicubic filtering.
+    pub fov_bicubic: bool,
+    /// Contrast adaptive sharpening at full magnification.
+    pub fov_sharpening: f32,
+    /// `CrosshairKind`.
+    pub crosshair_kind: u32,
+    /// `CrosshairBlend`.
+    pub crosshair_blend: u32,
+    pub crosshair_opacity: f32,
+    pub crosshair_scale: [f32; 2],
+    pub crosshair_color: [f32; 3],
+    /// Outline color of the "solid_outline" blend.
+    pub crosshair_outline_color: [f32; 3],
+    /// `CrosshairShape` of the custom crosshair, with sizes in pixels at 1080p.
+    pub crosshair_dot_radius: u32,
+    pub crosshair_line_length: u32,
+    pub crosshair_line_gap: u32,
+    pub crosshair_line_thickness: u32,
+    pub crosshair_ring_radius: u32,
+    pub crosshair_outline_width: u32,
+    pub crosshair_t_style: bool,
+    /// Rotation of the custom crosshair, in eighths of a turn clockwise.
+    pub crosshair_rotation: u32,
+    /// Scale of the game's chromatic aberration, applied to `cbToneMap` by
+    /// `fisheye_distortion_cb_hook`.
+    pub chromatic_aberration: f32,
+    /// Directions of the threat markers, in radians clockwise from the top of the screen.
+    pub threat_angles: [f32; MAX_MARKERS],
+    /// Opacity of the threat markers.
+    pub threat_alphas: [f32; MAX_MARKERS],
+    /// Intensity of the comfort tunnel vision vignette.
+    pub tunnel_vision: f32,
+}
+
+impl ShaderSettings {
+    pub const INITIAL: Self = Self {
+        fov_correction: false,
+        fov_projection: 0,
+        fov_correction_params: [0.0; 2],
+        aspect_ratio: 16.0 / 9.0,
+        fov_bicubic: false,
+        fov_sharpening: 0.0,
+        crosshair_kind: 0,
+        crosshair_blend: 0,
+        crosshair_opacity: 0.0,
+        crosshair_scale: [1.0; 2],
+        crosshair_color: [1.0; 3],
+        crosshair_outline_color: [0.0; 3],
+        crosshair_dot_radius: 0,
+        crosshair_line_length: 0,
+        crosshair_line_gap: 0,
+        crosshair_line_thickness: 1,
+        crosshair_ring_radius: 0,
+        crosshair_outline_width: 0,
+        crosshair_t_style: false,
+        crosshair_rotation: 0,
+        chromatic_aberration: 1.0,
+        threat_angles: [0.0; MAX_MARKERS],
+        threat_alphas: [0.0; MAX_MARKERS],
+        tunnel_vision: 0.0,
+    };
+
+    pub fn pack(&self) -> ShaderPayload {
+        type P = ShaderPayload;
+
+        let flags = P::VERSION.pack(PAYLOAD_VERSION)
+            | P::FOV_CORRECTION.pack(self.fov_correction as u32)
+            | P::FOV_PROJECTION.pack(self.fov_projection)
+            | P::FOV_BICUBIC.pack(self.fov_bicubic as u32)
+            | P::FOV_SHARPENING.pack_unorm(self.fov_sharpening)
+            | P::CROSSHAIR_KIND.pack(self.crosshair_kind)
+            | P::CROSSHAIR_BLEND.pack(self.crosshair_blend)
+            | P::CROSSHAIR_OPACITY.pack_unorm(self.crosshair_opacity)
+            | P::TUNNEL_VISION.pack_unorm(self.tunnel_vision)
+            | P::CROSSHAIR_T_STYLE.pack(self.crosshair_t_style as u32)
+            | P::CROSSHAIR_ROTATION.pack(self.crosshair_rotation)
+            | P::CROSSHAIR_OUTLINE.pack(self.crosshair_outline_width);
+
+        let [x, y] = self.fov_correction_params.map(f16_bits);
+
+        let [scale_x, scale_y] = self.crosshair_scale.map(|scale| {
+            ((scale.log2() - CROSSHAIR_SCALE_LOG2_MIN) * CROSSHAIR_SCALE_STEPS).round() as u32
+        });
+
+        let [red, green, blue] = self.crosshair_color;
+
+        let crosshair = P::CROSSHAIR_SCALE_X.pack(scale_x)
+            | P::CROSSHAIR_SCALE_Y.pack(scale_y)
+            | P::CROSSHAIR_RED.pack_unorm(red)
+            | P::CROSSHAIR_GREEN.pack_unorm(green)
+            | P::CROSSHAIR_BLUE.pack_unorm(blue);
+
+        let crosshair_shape = P::CROSSHAIR_DOT.pack(self.crosshair_dot_radius)
+            | P::CROSSHAIR_LENGTH.pack(self.crosshair_line_length)
+            | P::CROSSHAIR_GAP.pack(self.crosshair_line_gap)
+            | P::CROSSHAIR_THICKNESS.pack(self.crosshair_line_thickness.saturating_sub(1))
+            | P::CROSSHAIR_RING.pack(self.crosshair_ring_radius);
+
+        let [red, green, blue] = self.crosshair_outline_color;
+
+        let outline = P::OUTLINE_RED.pack_unorm(red)
+            | P::OUTLINE_GREEN.pack_unorm(green)
+            | P::OUTLINE_BLUE.pack_unorm(blue);
+
+        ShaderPayload {
+            flags,
+            params: [
+                P::FOV_CORRECTION_X.pack(x) | P::FOV_CORRECTION_Y.pack(y),
+                crosshair,
+            ],
+            markers: [
+                pack_markers(&self.threat_angles, &self.threat_alphas),
+                crosshair_shape | outline,
+            ],
+        }
+    }
+}
+
+/// Packs the markers of a word, leaving hidden markers zeroed so that the shader can skip
+/// a word without any.
+fn pack_markers(angles: &[f32; MAX_MARKERS], alphas: &[f32; MAX_MARKERS]) -> u32 {
+    let steps = (ShaderPayload::MARKER_ANGLE.max() + 1) as i32;
+
+    iter::zip(angles, alphas)
+        .enumerate()
+        .map(|(i, (&angle, &alpha))| {
+            let shift = i as u32 * ShaderPayload::MARKER_STRIDE;
+            let alpha = ShaderPayload::MARKER_ALPHA.shifted(shift).pack_unorm(alpha);
+
+            if alpha == 0 {
+                return 0;
+            }
+
+            let angle = ((angle / TAU * steps as f32).round() as i32).rem_euclid(steps);
+            alpha
+                | ShaderPayload::MARKER_ANGLE
+                    .shifted(shift)
+                    .pack(angle as u32)
+        })
+        .fold(0, |word, marker| word | marker)
+}
+
+/// Bits of the half float nearest to `value`, unpacked with `f16tof32` in the shader.
+pub fn f16_bits(value: f32) -> u32 {
+    let bits = value.to_bits();
+    let sign = (bits >> 16) & 0x8000;
+
+    if value.is_nan() {
+        return sign | 0x7e00;
+    }
+
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x7f_ffff;
+
+    let half = match exponent {
+        // Too small for a subnormal half float.
+        ..-10 => 0,
+        // Subnormal, in units of 2^-24.
+        -10..=0 => round_shift(mantissa | 0x80_0000, (14 - exponent) as u32),
+        // Rounding up carries into the exponent, up to infinity.
+        1..0x1f => round_shift((exponent as u32) << 23 | mantissa, 13),
+        _ => 0x7c00,
+    };
+
+    sign | half
+}
+
+/// Value of the bits of a half float.
+pub fn f16_value(bits: u32) -> f32 {
+    let sign = match bits & 0x8000 {
+        0 => 1.0,
+        _ => -1.0,
+    };
+
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    sign * match exponent {
+        0 => mantissa * 2.0f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2.0f32.powi(exponent as i32 - 15),
+    }
+}
+
+/// Rounds `value` to the nearest half float, as seen by the shader.
+pub fn f16_round(value: f32) -> f32 {
+    f16_value(f16_bits(value))
+}
+
+/// Shifts right, rounding to the nearest even.
+fn round_shift(value: u32, shift: u32) -> u32 {
+    let half = 1 << (shift - 1);
+    let rest = value & ((1 << shift) - 1);
+    let value = value >> shift;
+
+    value + (rest > half || rest == half && value & 1 != 0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TONE_MAP_HOOK: &str = include_str!("../../shaders/ToneMap_PostHook.hlsl");
+
+    /// Offset of the next member following the HLSL packing rules for constant buffers.
+    fn hlsl_offset(end: usize, size: usize) -> usize {
+        if end / 16 == (end + size - 1) / 16 {
+            end
+        } else {
+            end.next_multiple_of(16)
+        }
+    }
+
+    #[test]
+    fn slots_match_hlsl() {
+        let cb_tone_map = TONE_MAP_HOOK
+            .split_once("cbuffer cbToneMap")
+            .and_then(|(_, rest)| rest.split_once("};"))
+            .unwrap()
+            .0;
+
+        let mut members = Vec::new();
+        let mut end: usize = 0;
+
+        for member in cb_tone_map
+            .lines()
+            .filter_map(|line| line.trim().strip_suffix(';'))
+        {
+            let (hlsl_type, _) = member.split_once(' ').unwrap();
+
+            let size = match hlsl_type {
+                "uint" | "int" | "float" => 4,
+                "uint2" | "float2" => 8,
+                "float3" => 12,
+                "uint4" | "int4" | "float4" => 16,
+                // Column major, as three registers.
+                "float4x3" => 48,
+                _ => panic!("unsupported type {hlsl_type}"),
+            };
+
+            let offset = match hlsl_type {
+                "float4x3" => end.next_multiple_of(16),
+                _ => hlsl_offset(end, size),
+            };
+
+            members.push((member, offset));
+            end = offset + size;
+        }
+
+        assert_eq!(end, CB_TONE_MAP_SIZE);
+
+        for slot in &PAYLOAD_SLOTS {
+            let member = format!("{} {}", slot.hlsl_type, slot.name);
+            let index = members.iter().position(|&(m, _)| m == member).unwrap();
+
+            assert_eq!(members[index].1, slot.cb_offset, "{}", slot.name);
+            assert_eq!(members[index - 1].0, slot.after, "{}", slot.name);
+        }
+    }
+
+    #[test]
+    fn bit_fields_fit_words() {
+        for (fields, size) in ShaderPayload::WORDS {
+            let mut end = 0;
+
+            for field in fields {
+                assert!(field.offset >= end, "{}", field.name);
+                end = field.offset + field.bits;
+            }
+
+            assert!(end <= size);
+        }
+    }
+
+    #[test]
+    fn pack_round_trips() {
+        let settings = ShaderSettings {
+            fov_correction: true,
+            fov_projection: 3,
+            fov_correction_params: [0.4, 1.25],
+            crosshair_kind: 4,
+            crosshair_blend: 2,
+            crosshair_opacity: 1.0,
+            crosshair_scale: [1.0, 2.0],
+            crosshair_outline_color: [0.0, 1.0, 0.0],
+            crosshair_line_length: 20,
+            crosshair_line_thickness: 3,
+            crosshair_t_style: true,
+            crosshair_rotation: 2,
+            threat_angles: [-3.0, 0.5, 2.0],
+            threat_alphas: [1.0, 0.0, 0.5],
+            ..ShaderSettings::INITIAL
+        };
+
+        let payload = settings.pack();
+
+        assert_eq!(
+            ShaderPayload::VERSION.unpack(payload.flags),
+            PAYLOAD_VERSION
+        );
+        assert_eq!(ShaderPayload::FOV_PROJECTION.unpack(payload.flags), 3);
+        assert_eq!(ShaderPayload::CROSSHAIR_KIND.unpack(payload.flags), 4);
+        assert_eq!(ShaderPayload::CROSSHAIR_BLEND.unpack(payload.flags), 2);
+
+        assert_eq!(ShaderPayload::CROSSHAIR_T_STYLE.unpack(payload.flags), 1);
+        assert_eq!(ShaderPayload::CROSSHAIR_ROTATION.unpack(payload.flags), 2);
+
+        let shape = payload.markers[1];
+        assert_eq!(ShaderPayload::CROSSHAIR_LENGTH.unpack(shape), 15);
+        assert_eq!(ShaderPayload::CROSSHAIR_THICKNESS.unpack(shape), 2);
+        assert_eq!(ShaderPayload::CROSSHAIR_DOT.unpack(shape), 0);
+        assert_eq!(ShaderPayload::OUTLINE_RED.unpack(shape), 0);
+        assert_eq!(ShaderPayload::OUTLINE_GREEN.unpack(shape), 63);
+
+        let y = ShaderPayload::FOV_CORRECTION_Y.unpack(payload.params[0]);
+        assert_eq!(f16_value(y), 1.25);
+
+        let scale_y = ShaderPayload::CROSSHAIR_SCALE_Y.unpack(payload.params[1]) as f32;
+        let scale_y = (scale_y / CROSSHAIR_SCALE_STEPS + CROSSHAIR_SCALE_LOG2_MIN).exp2();
+        assert!((scale_y - 2.0).abs() < 1e-5);
+
+        let markers = payload.markers[0];
+        let angle = ShaderPayload::MARKER_ANGLE.unpack(markers) as f32 / 64.0 * TAU;
+        assert!((angle - (TAU - 3.0)).abs() <= TAU / 128.0);
+
+        let hidden = ShaderPayload::MARKER_ANGLE.shifted(ShaderPayload::MARKER_STRIDE);
+        assert_eq!(hidden.unpack(markers), 0);
+
+        let alpha = ShaderPayload::MARKER_ALPHA.shifted(2 * ShaderPayload::MARKER_STRIDE);
+        assert_eq!(alpha.unpack(markers), 8);
+    }
+
+    #[test]
+    fn f16_round_trips() {
+        for value in [0.0, 1.0, -2.5, 0.1, 65504.0, 1e6] {
+            let rounded = f16_round(value);
+            assert!((rounded - value).abs() <= value.abs() / 2048.0 || value > 65504.0);
+        }
+
+        assert_eq!(f16_bits(1.0), 0x3c00);
+        assert_eq!(f16_bits(-2.0), 0xc000);
+        assert_eq!(f16_bits(1e6), 0x7c00);
+        assert_eq!(f16_value(0x0001), 2.0f32.powi(-24));
+    }
+
+    #[test]
+    fn hlsl_declaration() {
+        let hlsl = ShaderPayload::hlsl_declaration();
+
+        assert!(hlsl.contains(&format!(
+            "#define ERFPS_PAYLOAD_VERSION {PAYLOAD_VERSION}\n"
+        )));
+        assert!(hlsl.contains("#define ERFPS_FOV_PROJECTION 5, 3\n"));
+        assert!(hlsl.contains("#define ERFPS_CROSSHAIR_SCALE_LOG2_MIN -5.0\n"));
+        assert!(hlsl.contains("uint ErfpsBits(uint word, uint offset, uint bits)\n"));
+    }
+}
@@ -1,79 +1,277 @@
 use glam::Vec2;
 
-use crate::shaders::{get_fov_correction, get_shader_flag};
+use crate::{
+    config::FovProjection,
+    shaders::{
+        distortion::{FovCorrection, perspective_radius},
+        get_aspect_ratio, get_fov_correction,
+    },
+};
+
+/// The assumed layout resolution of the HUD, 1920x1080 extended to the aspect ratio of the game.
+///
+/// `CSFeManImp` positions are assumed to be in the coordinates of a HUD layout authored at
+/// 1920x1080 and scaled uniformly to the screen, which makes them exact at 16:9. Other aspect
+/// ratios are assumed to keep the shorter side of the layout and extend the longer one, as a
+/// HUD that is neither cropped nor stretched would. Neither has been checked in game at
+/// ultrawide or 16:10 resolutions.
+pub fn hud_layout_size() -> Vec2 {
+    layout_size(get_aspect_ratio())
+}
+
+fn layout_size(aspect_ratio: f32) -> Vec2 {
+    const FE_XY: Vec2 = Vec2::new(1920.0, 1080.0);
+
+    if aspect_ratio >= FE_XY.x / FE_XY.y {
+        Vec2::new(FE_XY.y * aspect_ratio, FE_XY.y)
+    } else {
+        Vec2::new(FE_XY.x, FE_XY.x / aspect_ratio)
+    }
+}
 
 pub fn correct_screen_coords(xy: Vec2) -> Vec2 {
-    if !get_shader_flag(0) {
+    let Some(correction) = get_fov_correction() else {
         return xy;
-    }
+    };
 
     let xy = xy.clamp(Vec2::ZERO, Vec2::ONE);
-    if get_shader_flag(1) {
-        correct_screen_coords_barrel(xy)
-    } else {
-        correct_screen_coords_fisheye(xy)
-    }
+    correction.correct_screen_coords(xy)
 }
 
-fn correct_screen_coords_fisheye(uv: Vec2) -> Vec2 {
-    let uv = uv - 0.5;
+impl FovCorrection {
+    /// Maps the game's screen coordinates to corrected screen coordinates,
+    /// the inverse of [`FovCorrection::map_uv`].
+    pub fn correct_screen_coords(&self, xy: Vec2) -> Vec2 {
+        match self.projection {
+            FovProjection::Fisheye => self.correct_screen_coords_fisheye(xy),
+            FovProjection::Barrel => self.correct_screen_coords_barrel(xy),
+            FovProjection::Panini => self.correct_screen_coords_panini(xy),
+            FovProjection::Stereographic => self.correct_screen_coords_stereographic(xy),
+            FovProjection::Cylindrical => self.correct_screen_coords_cylindrical(xy),
+        }
+    }
+
+    fn correct_screen_coords_fisheye(&self, uv: Vec2) -> Vec2 {
+        let uv = uv - 0.5;
+
+        let strength = self.params.y;
+        let k_max = 1.0 + strength * 0.125;
+
+        let uvk = uv * k_max;
+
+        if uvk.x == 0.0 {
+            return uvk + 0.5;
+        }
+
+        let x = uvk.x.abs();
+        let mut y = x + 0.07 * (strength * x * x) - 0.5 * (strength * x * x) * x;
+
+        let f = |y: f32| (strength * (y * y)) * (y * y) + y - x;
+        let f_prime = |y: f32| 4.0 * (strength * (y * y)) * y + 1.0;
+
+        // A single Newton step is not enough at high strength and FOV.
+        for _ in 0..4 {
+            y -= f(y) / f_prime(y);
+        }
+
+        let uv = Vec2::new(y.copysign(uvk.x), uvk.y * y / x);
+
+        uv + 0.5
+    }
+
+    // Source: https://www.decarpentier.nl/lens-distortion
+    //
+    // Copyright (c) 2015, Giliam de Carpentier
+    // All rights reserved.
+    //
+    // Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+    //
+    // 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+    //
+    // 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the
+    // documentation and/or other materials provided with the distribution.
+    //
+    // THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED
+    // TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+    // CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+    // PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+    // LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+    // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+    fn correct_screen_coords_barrel(&self, xy: Vec2) -> Vec2 {
+        let aspect_ratio = self.aspect_ratio;
+
+        let Vec2 {
+            x: cylindrical_ratio,
+            y: strength_width_ratio,
+        } = self.params;
+        let scaled_height = strength_width_ratio / aspect_ratio;
+        let cyl_aspect_ratio = aspect_ratio * cylindrical_ratio;
+        let cyl_aspect_ratio_sq = cyl_aspect_ratio * cyl_aspect_ratio;
+        let aspect_diag_sq = aspect_ratio * aspect_ratio + 1.0;
+        let diag_sq = scaled_height * scaled_height * aspect_diag_sq;
+        let signed_uv = 2.0 * xy - 1.0;
+
+        let z = 0.5 * (diag_sq + 1.0).sqrt() + 0.5;
+        let ny = (z - 1.0) / (cyl_aspect_ratio_sq + 1.0);
+        let nx = cyl_aspect_ratio_sq * ny;
+        let p_sq = signed_uv * signed_uv;
+        let ivp = (0.25 + z * (nx * p_sq.x + ny * p_sq.y)).sqrt();
+
+        z * signed_uv / (0.5 + ivp) * 0.5 + 0.5
+    }
+
+    /// Inverse of `MapUvPanini`.
+    fn correct_screen_coords_panini(&self, uv: Vec2) -> Vec2 {
+        let Vec2 {
+            x: d,
+            y: half_width,
+        } = self.params;
+        let rect = self.rectilinear_coords(uv);
 
-    let strength = get_fov_correction().1;
-    let k_max = 1.0 + strength * 0.125;
+        let longitude = rect.x.atan();
+        let s = (d + 1.0) / (d + longitude.cos());
 
-    let uvk = uv * k_max;
+        let p = Vec2::new(s * longitude.sin(), s * rect.y * longitude.cos());
+        self.projection_uv(p, perspective_radius(half_width.atan().cos(), d))
+    }
+
+    /// Inverse of `MapUvStereographic`.
+    fn correct_screen_coords_stereographic(&self, uv: Vec2) -> Vec2 {
+        let Vec2 {
+            x: d,
+            y: half_width,
+        } = self.params;
+        let rect = self.rectilinear_coords(uv);
 
-    if uvk.x == 0.0 {
-        return uvk + 0.5;
+        let r = rect.length();
+        if r < 1e-6 {
+            return uv;
+        }
+
+        let p = rect * perspective_radius(r.atan().cos(), d) / r;
+        self.projection_uv(p, perspective_radius(half_width.atan().cos(), d))
     }
 
-    let x = uvk.x.abs();
-    let mut y = x + 0.07 * (strength * x * x) - 0.5 * (strength * x * x) * x;
+    /// Inverse of `MapUvCylindrical`.
+    fn correct_screen_coords_cylindrical(&self, uv: Vec2) -> Vec2 {
+        let Vec2 {
+            x: strength,
+            y: half_width,
+        } = self.params;
+        let rect = self.rectilinear_coords(uv);
 
-    let f = |y: f32| (strength * (y * y)) * (y * y) + y - x;
-    let f_prime = |y: f32| 4.0 * (strength * (y * y)) * y + 1.0;
+        let k = (1.0 - strength).max(1e-3);
 
-    y -= f(y) / f_prime(y);
+        let longitude = rect.x.atan();
+        let c = longitude.cos();
 
-    let uv = Vec2::new(y.copysign(uvk.x), uvk.y * y / x);
+        let p = Vec2::new((k * longitude).tan() / k, rect.y * c / c.powf(k));
+        self.projection_uv(p, (k * half_width.atan()).tan() / k)
+    }
 
-    uv + 0.5
+    /// Inverse of `RectilinearUv`.
+    fn rectilinear_coords(&self, uv: Vec2) -> Vec2 {
+        (uv * 2.0 - 1.0) * Vec2::new(1.0, self.aspect_ratio.recip()) * self.params.y
+    }
+
+    /// Inverse of `ProjectionCoords`.
+    fn projection_uv(&self, p: Vec2, edge: f32) -> Vec2 {
+        p / (Vec2::new(1.0, self.aspect_ratio.recip()) * edge) * 0.5 + 0.5
+    }
 }
 
-// Source: https://www.decarpentier.nl/lens-distortion
-//
-// Copyright (c) 2015, Giliam de Carpentier
-// All rights reserved.
-//
-// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
-//
-// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
-//
-// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the
-// documentation and/or other materials provided with the distribution.
-//
-// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED
-// TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
-// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
-// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
-// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
-// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-fn correct_screen_coords_barrel(xy: Vec2) -> Vec2 {
-    const ASPECT_RATIO: f32 = 16.0 / 9.0;
-
-    let (cylindrical_ratio, strength_width_ratio) = get_fov_correction();
-    let scaled_height = strength_width_ratio / ASPECT_RATIO;
-    let cyl_aspect_ratio = ASPECT_RATIO * cylindrical_ratio;
-    let cyl_aspect_ratio_sq = cyl_aspect_ratio * cyl_aspect_ratio;
-    let aspect_diag_sq = ASPECT_RATIO * ASPECT_RATIO + 1.0;
-    let diag_sq = scaled_height * scaled_height * aspect_diag_sq;
-    let signed_uv = 2.0 * xy - 1.0;
-
-    let z = 0.5 * (diag_sq + 1.0).sqrt() + 0.5;
-    let ny = (z - 1.0) / (cyl_aspect_ratio_sq + 1.0);
-    let nx = cyl_aspect_ratio_sq * ny;
-    let p_sq = signed_uv * signed_uv;
-    let ivp = (0.25 + z * (nx * p_sq.x + ny * p_sq.y)).sqrt();
-
-    z * signed_uv / (0.5 + ivp) * 0.5 + 0.5
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use crate::{config::FovProjection, shaders::distortion::FovCorrection};
+
+    use super::layout_size;
+
+    const PROJECTIONS: [FovProjection; 5] = [
+        FovProjection::Fisheye,
+        FovProjection::Barrel,
+        FovProjection::Panini,
+        FovProjection::Stereographic,
+        FovProjection::Cylindrical,
+    ];
+
+    const ASPECT_RATIOS: [f32; 4] = [16.0 / 10.0, 16.0 / 9.0, 21.0 / 9.0, 32.0 / 9.0];
+
+    const TOLERANCE: f32 = 1e-4;
+
+    fn check_inverse(correction: &FovCorrection) {
+        const STEPS: u32 = 8;
+
+        for y in 0..=STEPS {
+            for x in 0..=STEPS {
+                let uv = Vec2::new(x as f32, y as f32) / STEPS as f32;
+                let corrected = correction.correct_screen_coords(correction.map_uv(uv));
+
+                assert!(
+                    corrected.abs_diff_eq(uv, TOLERANCE),
+                    "{correction:?}: {uv} -> {corrected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn correct_screen_coords_inverts_map_uv() {
+        // The same ranges as the config (see `Config::from`).
+        let strengths = (1..=20).map(|i| i as f32 / 20.0);
+        let cylindricities = (0..=4).map(|i| i as f32 / 4.0 * 1.5 + 0.5);
+        let fovs = (45..=130)
+            .step_by(5)
+            .map(|degrees| (degrees as f32).to_radians());
+
+        for projection in PROJECTIONS {
+            for strength in strengths.clone() {
+                for cylindricity in cylindricities.clone() {
+                    for fov in fovs.clone() {
+                        for aspect_ratio in ASPECT_RATIOS {
+                            check_inverse(&FovCorrection::new(
+                                projection,
+                                strength,
+                                cylindricity,
+                                fov,
+                                aspect_ratio,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn correct_screen_coords_keeps_center() {
+        let center = Vec2::splat(0.5);
+
+        for projection in PROJECTIONS {
+            for aspect_ratio in ASPECT_RATIOS {
+                let correction =
+                    FovCorrection::new(projection, 1.0, 2.0, 130f32.to_radians(), aspect_ratio);
+
+                assert!(correction.map_uv(center).abs_diff_eq(center, TOLERANCE));
+                assert!(
+                    correction
+                        .correct_screen_coords(center)
+                        .abs_diff_eq(center, TOLERANCE)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn layout_size_matches_aspect_ratio() {
+        assert_eq!(layout_size(16.0 / 9.0), Vec2::new(1920.0, 1080.0));
+
+        for aspect_ratio in ASPECT_RATIOS {
+            let size = layout_size(aspect_ratio);
+
+            assert!((size.x / size.y - aspect_ratio).abs() < TOLERANCE);
+            assert!(size.cmpge(Vec2::new(1920.0, 1080.0)).all());
+        }
+    }
 }